@@ -0,0 +1,6 @@
+fn main() {
+    // Templates and extras are embedded with `include_dir!`, so rebuild the
+    // binary whenever anything under them changes.
+    println!("cargo:rerun-if-changed=templates");
+    println!("cargo:rerun-if-changed=extras");
+}
@@ -0,0 +1,60 @@
+terraform {
+  required_version = ">= 1.5"
+
+  required_providers {
+    docker = {
+      source  = "kreuzwerker/docker"
+      version = "~> 3.0"
+    }
+  }
+}
+
+provider "docker" {}
+
+variable "image_tag" {
+  description = "Tag of the service images to run"
+  type        = string
+  default     = "latest"
+}
+
+# Every service of the project: HTTP ones with the host port they are
+# published on, workers (which serve nothing) without one
+locals {
+  services = {
+{% for service in services if service not in workers %}
+    {{ service }} = { kind = "http", port = {{ 8080 + loop.index0 }} }
+{% endfor %}
+{% for service in workers %}
+    {{ service }} = { kind = "worker", port = null }
+{% endfor %}
+  }
+}
+
+resource "docker_image" "service" {
+  for_each     = local.services
+  name         = "${each.key}:${var.image_tag}"
+  keep_locally = true
+}
+
+resource "docker_container" "service" {
+  for_each = local.services
+  name     = each.key
+  image    = docker_image.service[each.key].image_id
+  env      = each.value.kind == "http" ? ["SERVER_ADDRESS=0.0.0.0:8080"] : []
+
+  dynamic "ports" {
+    for_each = each.value.kind == "http" ? [each.value.port] : []
+    content {
+      internal = 8080
+      external = ports.value
+    }
+  }
+}
+
+output "urls" {
+  description = "Where each service answers"
+  value = {
+    for name, service in local.services : name => "http://localhost:${service.port}"
+    if service.kind == "http"
+  }
+}
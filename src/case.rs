@@ -0,0 +1,102 @@
+/// Split an identifier into lowercase words.
+///
+/// Words are separated by any non-alphanumeric character and by
+/// lower-to-upper case transitions, so `my-api`, `my_api`, `MyApi` and
+/// `my api` all yield `["my", "api"]`.
+fn words(input: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+
+    for c in input.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// `my-api` -> `my_api`
+pub fn snake_case(input: &str) -> String {
+    words(input).join("_")
+}
+
+/// `my_api` -> `my-api`
+pub fn kebab_case(input: &str) -> String {
+    words(input).join("-")
+}
+
+/// `my-api` -> `MyApi`
+pub fn pascal_case(input: &str) -> String {
+    words(input)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// `todo` -> `todos`, `category` -> `categories`, `box` -> `boxes`
+///
+/// Only the last word changes; irregular nouns are left to the user.
+pub fn plural(input: &str) -> String {
+    let word = snake_case(input);
+    let consonant_y = word.len() > 1
+        && word.ends_with('y')
+        && !word[..word.len() - 1].ends_with(['a', 'e', 'i', 'o', 'u']);
+    if consonant_y {
+        format!("{}ies", &word[..word.len() - 1])
+    } else if word.ends_with(['s', 'x', 'z']) || word.ends_with("ch") || word.ends_with("sh") {
+        format!("{}es", word)
+    } else {
+        format!("{}s", word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_spelling_splits_into_the_same_words() {
+        for input in ["my-api", "my_api", "MyApi", "my api", "myApi", "MY-API"] {
+            assert_eq!(snake_case(input), "my_api", "{}", input);
+            assert_eq!(kebab_case(input), "my-api", "{}", input);
+            assert_eq!(pascal_case(input), "MyApi", "{}", input);
+        }
+    }
+
+    #[test]
+    fn digits_stay_with_their_word() {
+        assert_eq!(snake_case("oauth2Client"), "oauth2_client");
+        assert_eq!(pascal_case("api-v2"), "ApiV2");
+        assert_eq!(kebab_case("__Todo__Item__"), "todo-item");
+    }
+
+    #[test]
+    fn plural_follows_english_endings() {
+        assert_eq!(plural("todo"), "todos");
+        assert_eq!(plural("category"), "categories");
+        assert_eq!(plural("day"), "days");
+        assert_eq!(plural("box"), "boxes");
+        assert_eq!(plural("match"), "matches");
+        assert_eq!(plural("wish"), "wishes");
+        assert_eq!(plural("BlogEntry"), "blog_entries");
+    }
+}
@@ -0,0 +1,183 @@
+use std::path::Path;
+
+use crate::component::Component;
+use crate::error::Error;
+use crate::generator::{GENERATORS_DIR, Generator};
+use crate::manifest::{MANIFEST_FILE, VarKind, Variable};
+use crate::render::{Context, Renderer};
+use crate::template::Origin;
+use crate::template::Template;
+use crate::workspace::WORKSPACE_DIR;
+
+/// `list`: every template with its description.
+pub fn list(templates: &[Template]) {
+    let width = templates.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    for template in templates {
+        let origin = match template.origin {
+            Origin::Builtin => String::new(),
+            ref origin => format!("  ({})", origin),
+        };
+        println!(
+            "{:<width$}  {}{}",
+            template.name,
+            template.manifest.template.description,
+            origin,
+            width = width
+        );
+    }
+}
+
+/// `info <template>`: description, options, files and dependencies.
+pub fn info(template: &Template) -> Result<(), Error> {
+    let manifest = &template.manifest;
+    println!("{}", template.name);
+    println!("  {}", manifest.template.description);
+    if template.origin != Origin::Builtin {
+        println!("  {}", template.origin);
+    }
+    if !manifest.template.aliases.is_empty() {
+        println!("  aliases: {}", manifest.template.aliases.join(", "));
+    }
+
+    println!();
+    println!("Options:");
+    for variable in &manifest.variables {
+        println!("  --var {}", describe(variable));
+    }
+    for extra in &manifest.template.extras {
+        println!("  --with-{}", extra);
+    }
+
+    let components: Vec<String> = Component::all(template)
+        .iter()
+        .map(Component::name)
+        .collect();
+    if !components.is_empty() {
+        println!();
+        println!("Components (for `add`): {}", components.join(", "));
+    }
+
+    let generators = Generator::all(template)?;
+    if !generators.is_empty() {
+        println!();
+        println!("Generators (for `generate`):");
+        for generator in &generators {
+            let types: Vec<&str> = generator
+                .manifest
+                .types
+                .keys()
+                .map(String::as_str)
+                .collect();
+            println!("  {}  {}", generator.name, generator.manifest.description);
+            if !types.is_empty() {
+                println!("    field types: {}", types.join(", "));
+            }
+        }
+    }
+
+    println!();
+    println!("Files:");
+    for file in &template.files {
+        let path = file.path.as_path();
+        if path == Path::new(MANIFEST_FILE)
+            || path.starts_with(GENERATORS_DIR)
+            || path.starts_with(WORKSPACE_DIR)
+        {
+            continue;
+        }
+        let conditions: Vec<&str> = manifest
+            .rules_for(path)
+            .map(|rule| rule.when.as_str())
+            .collect();
+        if conditions.is_empty() {
+            println!("  {}", path.display());
+        } else {
+            println!("  {}  (when {})", path.display(), conditions.join(" and "));
+        }
+    }
+
+    println!();
+    println!("Dependencies (with default options):");
+    let dependencies = dependencies(template)?;
+    if dependencies.is_empty() {
+        println!("  (none)");
+    }
+    for (name, version) in dependencies {
+        println!("  {} {}", name, version);
+    }
+    Ok(())
+}
+
+/// `name=<string> (default: x)`, `features=a,b,... (choices: ...)`
+fn describe(variable: &Variable) -> String {
+    let kind = match variable.kind {
+        VarKind::String => "<string>",
+        VarKind::Bool => "<yes|no>",
+        VarKind::List => "<a,b,...>",
+    };
+    let mut line = format!("{}={}", variable.name, kind);
+    if let Some(prompt) = &variable.prompt {
+        line.push_str(&format!("  {}", prompt));
+    }
+    if !variable.choices.is_empty() {
+        line.push_str(&format!(" [choices: {}]", variable.choices.join(", ")));
+    }
+    if !variable.requires.is_empty() {
+        let requires: Vec<String> = variable
+            .requires
+            .iter()
+            .map(|(choice, required)| format!("{} needs {}", choice, required.join(", ")))
+            .collect();
+        line.push_str(&format!(" [{}]", requires.join("; ")));
+    }
+    match &variable.default {
+        Some(default) => line.push_str(&format!(" [default: {}]", default)),
+        None => line.push_str(" [required]"),
+    }
+    line
+}
+
+/// Dependencies of the template's `Cargo.toml`, rendered with default
+/// variable values.
+fn dependencies(template: &Template) -> Result<Vec<(String, String)>, Error> {
+    let Some(file) = template.file("Cargo.toml") else {
+        return Ok(Vec::new());
+    };
+
+    let mut context = Context::new("example", "author");
+    context.extend(
+        template
+            .manifest
+            .variables
+            .iter()
+            .filter_map(|v| v.default.clone().map(|d| (v.name.clone(), d))),
+    );
+    let render_error = |message: String| Error::Render {
+        path: template.root().join(&file.path),
+        message,
+    };
+    let rendered = Renderer::new(&context)
+        .render_str(std::str::from_utf8(&file.contents).unwrap_or_default())
+        .map_err(|e| render_error(e.to_string()))?;
+    let manifest: toml::Table =
+        toml::from_str(&rendered).map_err(|e| render_error(e.to_string()))?;
+
+    let Some(deps) = manifest.get("dependencies").and_then(|d| d.as_table()) else {
+        return Ok(Vec::new());
+    };
+    Ok(deps
+        .iter()
+        .map(|(name, spec)| {
+            let version = match spec {
+                toml::Value::String(version) => version.clone(),
+                toml::Value::Table(table) => table
+                    .get("version")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("*")
+                    .to_string(),
+                _ => "*".to_string(),
+            };
+            (name.clone(), version)
+        })
+        .collect())
+}
@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use toml_edit::DocumentMut;
+
+use crate::error::Error;
+use crate::generate::{Outcome, Report};
+use crate::lock::{Lock, Snapshot};
+use crate::manifest::{Extra, VarKind, VarValue};
+use crate::plan::Plan;
+use crate::project::Project;
+use crate::render::{Context, Renderer};
+use crate::template::Template;
+use crate::upgrade::{self, Merged};
+use crate::workspace::Workspace;
+
+/// Something `add` can bolt onto an existing project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Component {
+    /// A choice of a list variable, e.g. `cache` of `features`.
+    Choice {
+        variable: String,
+        value: String,
+    },
+    Extra(Extra),
+}
+
+impl Component {
+    /// Every component of `template`: the choices of its list variables and
+    /// the extras it supports.
+    pub fn all(template: &Template) -> Vec<Component> {
+        let manifest = &template.manifest;
+        let choices = manifest
+            .variables
+            .iter()
+            .filter(|variable| variable.kind == VarKind::List)
+            .flat_map(|variable| {
+                variable.choices.iter().map(|value| Component::Choice {
+                    variable: variable.name.clone(),
+                    value: value.clone(),
+                })
+            });
+        let extras = manifest
+            .template
+            .extras
+            .iter()
+            .copied()
+            .map(Component::Extra);
+        choices.chain(extras).collect()
+    }
+
+    pub fn find(template: &Template, name: &str) -> Result<Component, Error> {
+        let all = Component::all(template);
+        let names: Vec<String> = all.iter().map(Component::name).collect();
+        all.into_iter()
+            .find(|component| component.name() == name)
+            .ok_or_else(|| {
+                let available = if names.is_empty() {
+                    "none".to_string()
+                } else {
+                    names.join(", ")
+                };
+                Error::Project(format!(
+                    "Template '{}' has no component '{}' (available: {})",
+                    template.name, name, available
+                ))
+            })
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Component::Choice { value, .. } => value.clone(),
+            Component::Extra(extra) => extra.to_string(),
+        }
+    }
+}
+
+/// Add `component` to the project in `root`.
+///
+/// The template is rendered twice, without and with the component, and only
+/// the difference is applied: files that appear are created, files that
+/// change are merged three ways like `upgrade` does (the render without the
+/// component is the common ancestor, the project's file ours and the render
+/// with it theirs), with conflict markers where the user changed the same
+/// lines, and dependencies that appear are added to `Cargo.toml` with its
+/// formatting preserved. A new file that is already there with other
+/// contents is not replaced; the generated version is written next to it as
+/// `<name>.new`.
+///
+/// The options recorded in the project's lock file are the starting point
+/// and the lock and base snapshot are updated to include the component.
+pub fn add(
+    project: &Project,
+    template: &Template,
+    component: &Component,
+    overrides: &[(String, String)],
+) -> Result<Report, Error> {
+    let root = &project.root;
+    let manifest = &template.manifest;
+    let lock = Lock::read(root)?;
+    let (name, author) = match &lock {
+        Some(lock) => (&lock.project.name, &lock.project.author),
+        None => (&project.name, &project.author),
+    };
+    let variables = match &lock {
+        Some(lock) => lock.variables(manifest, overrides)?,
+        None => manifest.resolve_variables(overrides)?,
+    };
+    let workspace = lock.as_ref().and_then(|lock| lock.project.workspace);
+    let workspace = workspace.as_ref();
+    let extras: Vec<Extra> = manifest
+        .template
+        .extras
+        .iter()
+        .copied()
+        .filter(|extra| match &lock {
+            Some(lock) => lock.project.extras.contains(extra),
+            None => root.join(extra.target_dir()).exists(),
+        })
+        .collect();
+
+    let (before, after, options, with) = match component {
+        Component::Choice { variable, value } => {
+            let mut before = variables.clone();
+            let mut after = variables;
+            let list = |values: &BTreeMap<String, VarValue>| match values.get(variable) {
+                Some(VarValue::List(items)) => items.clone(),
+                _ => Vec::new(),
+            };
+            let mut without = list(&before);
+            without.retain(|item| item != value);
+            let mut with = without.clone();
+            with.push(value.clone());
+            before.insert(variable.clone(), VarValue::List(without));
+            let with = match manifest.variables.iter().find(|v| &v.name == variable) {
+                Some(declared) => declared.complete(VarValue::List(with)),
+                None => VarValue::List(with),
+            };
+            after.insert(variable.clone(), with);
+            (
+                render(template, name, author, &before, &extras, workspace)?,
+                render(template, name, author, &after, &extras, workspace)?,
+                after,
+                extras,
+            )
+        }
+        Component::Extra(extra) => {
+            let mut with = extras.clone();
+            // Like --with-infra, infra brings CI along
+            let added = match extra {
+                Extra::Ci => vec![Extra::Ci],
+                Extra::Infra => vec![Extra::Ci, Extra::Infra],
+            };
+            for extra in added {
+                if !with.contains(&extra) {
+                    with.push(extra);
+                }
+            }
+            (
+                render(template, name, author, &variables, &extras, workspace)?,
+                render(template, name, author, &variables, &with, workspace)?,
+                variables,
+                with,
+            )
+        }
+    };
+
+    let mut changes: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    let mut report = Report::default();
+    let mut snapshot = Snapshot::read(root)?;
+    for file in &after.files {
+        let existing = project.read(&file.path);
+        let previous = before.files.iter().find(|f| f.path == file.path);
+        if previous.is_none_or(|previous| previous.contents != file.contents)
+            && let Some(snapshot) = &mut snapshot
+        {
+            snapshot.insert(&file.path, &file.contents);
+        }
+
+        let outcome = match (previous, existing) {
+            (Some(previous), _) if previous.contents == file.contents => continue,
+            // Part of the component, but the user removed the file
+            (Some(_), None) => Outcome::Skipped,
+            (Some(previous), Some(existing))
+                if file.path.file_name() == Some("Cargo.toml".as_ref()) =>
+            {
+                match patch_manifest(&existing, &previous.contents, &file.contents) {
+                    Some(patched) if patched != existing => {
+                        changes.push((file.path.clone(), patched));
+                        Outcome::Updated
+                    }
+                    _ => Outcome::Unchanged,
+                }
+            }
+            (Some(previous), Some(existing)) => {
+                match upgrade::merge(&previous.contents, &existing, &file.contents) {
+                    Merged::Clean(merged) if merged == existing => Outcome::Unchanged,
+                    Merged::Clean(merged) => {
+                        changes.push((file.path.clone(), merged));
+                        Outcome::Updated
+                    }
+                    Merged::Conflict(conflicted) => {
+                        changes.push((file.path.clone(), conflicted));
+                        Outcome::Conflict
+                    }
+                    Merged::Binary => {
+                        let copy = upgrade::new_copy(&file.path);
+                        changes.push((copy.clone(), file.contents.clone()));
+                        Outcome::NewCopy(copy)
+                    }
+                }
+            }
+            (None, None) => {
+                changes.push((file.path.clone(), file.contents.clone()));
+                Outcome::Created
+            }
+            (None, Some(existing)) if existing == file.contents => Outcome::Unchanged,
+            (None, Some(_)) => {
+                let copy = upgrade::new_copy(&file.path);
+                changes.push((copy.clone(), file.contents.clone()));
+                Outcome::NewCopy(copy)
+            }
+        };
+        report.entries.push((file.path.clone(), outcome));
+    }
+
+    project.write(changes)?;
+    if let Some(mut lock) = lock {
+        lock.options = options;
+        lock.project.extras = with;
+        lock.write(root)?;
+    }
+    if let Some(snapshot) = snapshot {
+        snapshot.write(root)?;
+    }
+    Ok(report)
+}
+
+fn render(
+    template: &Template,
+    name: &str,
+    author: &str,
+    variables: &BTreeMap<String, VarValue>,
+    extras: &[Extra],
+    workspace: Option<&Workspace>,
+) -> Result<Plan, Error> {
+    let mut context = Context::new(name, author);
+    context.set_options(variables, extras);
+    context.set_workspace(workspace);
+    Plan::build(template, extras, workspace, &Renderer::new(&context))
+}
+
+/// Add the dependencies `after` declares on top of `before` to `existing`,
+/// unless the project already has (or deliberately dropped) them.
+fn patch_manifest(existing: &[u8], before: &[u8], after: &[u8]) -> Option<Vec<u8>> {
+    let parse =
+        |bytes: &[u8]| -> Option<DocumentMut> { std::str::from_utf8(bytes).ok()?.parse().ok() };
+    let mut existing = parse(existing)?;
+    let (before, after) = (parse(before)?, parse(after)?);
+
+    let sections: [&[&str]; 4] = [
+        &["dependencies"],
+        &["dev-dependencies"],
+        &["build-dependencies"],
+        &["workspace", "dependencies"],
+    ];
+    for section in sections {
+        let get = |document: &DocumentMut| {
+            section
+                .iter()
+                .try_fold(document.as_item(), |item, key| item.get(key))
+                .and_then(|item| item.as_table_like())
+                .map(|table| {
+                    table
+                        .iter()
+                        .map(|(key, item)| (key.to_string(), item.clone()))
+                        .collect::<Vec<_>>()
+                })
+        };
+        let Some(added) = get(&after) else {
+            continue;
+        };
+        let previous = get(&before);
+        for (key, item) in added {
+            if previous
+                .as_ref()
+                .is_some_and(|previous| previous.iter().any(|(k, _)| *k == key))
+            {
+                continue;
+            }
+            let mut table = existing.as_item_mut();
+            for key in section {
+                table = table
+                    .as_table_like_mut()?
+                    .entry(key)
+                    .or_insert_with(toml_edit::table);
+            }
+            let table = table.as_table_like_mut()?;
+            if !table.contains_key(&key) {
+                table.insert(&key, item);
+            }
+        }
+    }
+    Some(existing.to_string().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE: &str = "[package]\nname = \"demo\"\n\n[dependencies]\nserde = \"1\"\n";
+    const AFTER: &str =
+        "[package]\nname = \"demo\"\n\n[dependencies]\nredis = \"0.27\"\nserde = \"1\"\n";
+
+    fn patch(existing: &str, before: &str, after: &str) -> String {
+        let patched = patch_manifest(existing.as_bytes(), before.as_bytes(), after.as_bytes());
+        String::from_utf8(patched.expect("the manifests parse")).unwrap()
+    }
+
+    #[test]
+    fn new_dependencies_are_added_keeping_the_rest() {
+        let existing = "[package]\nname = \"demo\"\nlicense = \"MIT\"\n\n[dependencies]\n# Ours\nserde = { version = \"1\", features = [\"derive\"] }\nanyhow = \"1\"\n";
+        assert_eq!(
+            patch(existing, BEFORE, AFTER),
+            format!("{}redis = \"0.27\"\n", existing)
+        );
+    }
+
+    #[test]
+    fn dependencies_the_user_changed_or_dropped_stay_that_way() {
+        let existing = "[package]\nname = \"demo\"\n\n[dependencies]\nredis = \"0.25\"\n";
+        assert_eq!(patch(existing, BEFORE, AFTER), existing);
+    }
+
+    #[test]
+    fn missing_tables_are_created() {
+        let existing = "[package]\nname = \"demo\"\n";
+        let patched = patch(existing, BEFORE, AFTER);
+        let cargo: toml::Table = patched.parse().unwrap();
+        assert_eq!(cargo["dependencies"]["redis"].as_str(), Some("0.27"));
+        assert!(cargo["dependencies"].get("serde").is_none());
+    }
+
+    #[test]
+    fn workspace_dependencies_are_added_too() {
+        let before = "[workspace]\nmembers = [\"crates/api\"]\n";
+        let after = "[workspace]\nmembers = [\"crates/api\"]\n\n[workspace.dependencies]\nredis = \"0.27\"\n";
+        let patched = patch(before, before, after);
+        assert_eq!(patched, after);
+    }
+
+    #[test]
+    fn manifests_that_do_not_parse_are_left_alone() {
+        let patched = patch_manifest(b"[package", BEFORE.as_bytes(), AFTER.as_bytes());
+        assert_eq!(patched, None);
+    }
+}
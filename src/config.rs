@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::manifest::{Manifest, VarValue};
+use crate::registry;
+
+/// File name of the user config, in [`registry::config_dir`].
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// File name of a project-local config, looked up from the current
+/// directory upwards. Its settings override the user config key by key.
+pub const LOCAL_CONFIG_FILE: &str = ".create_woragis.toml";
+
+/// Defaults for every invocation, and named presets selected with
+/// `--preset`.
+///
+/// ```toml
+/// [defaults]
+/// author = "Jane"
+/// database = "sqlite"
+///
+/// [presets.internal-service]
+/// template = "rest"
+/// features = ["auth", "cache"]
+/// ci = true
+/// vars = { docker = false }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub defaults: Settings,
+    #[serde(default)]
+    pub presets: BTreeMap<String, Settings>,
+}
+
+/// What a config can preset; every key is optional.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    pub author: Option<String>,
+    /// As with `--license`.
+    pub license: Option<String>,
+    pub template: Option<String>,
+    pub database: Option<String>,
+    pub framework: Option<String>,
+    pub features: Option<Vec<String>>,
+    /// Include GitHub Actions CI (`--with-ci`).
+    pub ci: Option<bool>,
+    /// Include Terraform (`--with-infra`).
+    pub infra: Option<bool>,
+    /// Other template variables, as with `--var`.
+    #[serde(default)]
+    pub vars: BTreeMap<String, VarValue>,
+}
+
+impl Config {
+    /// The user config overridden by the nearest project-local one; either
+    /// may be missing.
+    pub fn load() -> Result<Config, Error> {
+        let user = registry::config_dir().map(|dir| dir.join(CONFIG_FILE));
+        let local = std::env::current_dir()
+            .ok()
+            .and_then(|dir| find_upwards(&dir, LOCAL_CONFIG_FILE));
+        let mut config = Config::default();
+        for path in user.into_iter().chain(local) {
+            if path.is_file() {
+                config = Config::read(&path)?.over(config);
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn read(path: &Path) -> Result<Config, Error> {
+        let source = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        toml::from_str(&source)
+            .map_err(|e| Error::Config(format!("invalid {}: {}", path.display(), e)))
+    }
+
+    /// The defaults, overridden by `preset` if given.
+    pub fn settings(&self, preset: Option<&str>) -> Result<Settings, Error> {
+        let Some(name) = preset else {
+            return Ok(self.defaults.clone());
+        };
+        match self.presets.get(name) {
+            Some(preset) => Ok(preset.clone().over(self.defaults.clone())),
+            None => {
+                let available: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+                Err(Error::Config(format!(
+                    "Unknown preset '{}' (available: {})",
+                    name,
+                    if available.is_empty() {
+                        "none; define them under [presets.<name>] in config.toml".to_string()
+                    } else {
+                        available.join(", ")
+                    }
+                )))
+            }
+        }
+    }
+
+    /// `self` with `base` filling in what it leaves out.
+    fn over(self, mut base: Config) -> Config {
+        for (name, preset) in self.presets {
+            let merged = match base.presets.remove(&name) {
+                Some(previous) => preset.over(previous),
+                None => preset,
+            };
+            base.presets.insert(name, merged);
+        }
+        Config {
+            defaults: self.defaults.over(base.defaults),
+            presets: base.presets,
+        }
+    }
+}
+
+impl Settings {
+    /// `self` with `base` filling in what it leaves out.
+    pub fn over(self, base: Settings) -> Settings {
+        let mut vars = base.vars;
+        vars.extend(self.vars);
+        Settings {
+            author: self.author.or(base.author),
+            license: self.license.or(base.license),
+            template: self.template.or(base.template),
+            database: self.database.or(base.database),
+            framework: self.framework.or(base.framework),
+            features: self.features.or(base.features),
+            ci: self.ci.or(base.ci),
+            infra: self.infra.or(base.infra),
+            vars,
+        }
+    }
+
+    /// The settings as `KEY=VALUE` variables, keeping only those `manifest`
+    /// declares: the same config serves templates with different variables.
+    pub fn variables(&self, manifest: &Manifest) -> Vec<(String, String)> {
+        let named = [
+            ("database", self.database.clone()),
+            ("framework", self.framework.clone()),
+            ("features", self.features.as_ref().map(|f| f.join(","))),
+        ];
+        named
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| (key.to_string(), value)))
+            .chain(
+                self.vars
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.to_string())),
+            )
+            .filter(|(key, _)| manifest.variables.iter().any(|v| &v.name == key))
+            .collect()
+    }
+}
+
+fn find_upwards(start: &Path, file: &str) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(file))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::Template;
+
+    const USER: &str = r#"
+[defaults]
+author = "Jane"
+database = "sqlite"
+framework = "axum"
+vars = { docker = false, region = "eu" }
+
+[presets.svc]
+database = "postgres"
+features = ["auth"]
+"#;
+
+    fn config(source: &str) -> Config {
+        toml::from_str(source).unwrap()
+    }
+
+    #[test]
+    fn presets_override_the_defaults_key_by_key() {
+        let settings = config(USER).settings(Some("svc")).unwrap();
+        assert_eq!(settings.author.as_deref(), Some("Jane"));
+        assert_eq!(settings.database.as_deref(), Some("postgres"));
+        assert_eq!(settings.framework.as_deref(), Some("axum"));
+        assert_eq!(settings.features, Some(vec!["auth".to_string()]));
+
+        let defaults = config(USER).settings(None).unwrap();
+        assert_eq!(defaults.database.as_deref(), Some("sqlite"));
+        assert_eq!(defaults.features, None);
+    }
+
+    #[test]
+    fn unknown_presets_name_the_available_ones() {
+        let error = config(USER).settings(Some("nope")).unwrap_err();
+        assert!(error.to_string().contains("available: svc"), "{}", error);
+    }
+
+    #[test]
+    fn local_configs_override_the_user_one_key_by_key() {
+        let local =
+            config("[defaults]\ndatabase = \"mysql\"\n\n[presets.svc]\nfeatures = [\"cache\"]\n");
+        let merged = local.over(config(USER));
+
+        let defaults = merged.settings(None).unwrap();
+        assert_eq!(defaults.database.as_deref(), Some("mysql"));
+        assert_eq!(defaults.author.as_deref(), Some("Jane"));
+
+        let preset = merged.settings(Some("svc")).unwrap();
+        assert_eq!(preset.database.as_deref(), Some("postgres"));
+        assert_eq!(preset.features, Some(vec!["cache".to_string()]));
+    }
+
+    #[test]
+    fn flags_win_over_the_spec_the_preset_and_the_defaults() {
+        let template = Template::find(crate::DEFAULT_TEMPLATE).unwrap();
+        let manifest = &template.manifest;
+        // Layered as `run` does: the spec over the preset over the defaults,
+        // then the flags appended after the settings' variables
+        let spec = Settings {
+            framework: Some("actix".to_string()),
+            features: Some(vec!["cache".to_string()]),
+            ..Settings::default()
+        };
+        let settings = spec.over(config(USER).settings(Some("svc")).unwrap());
+        let mut overrides = settings.variables(manifest);
+        overrides.push(("framework".to_string(), "axum".to_string()));
+
+        // `region` is not a variable of the template and is left out
+        let values = manifest.resolve_variables(&overrides).unwrap();
+        assert_eq!(values["framework"].to_string(), "axum");
+        assert_eq!(values["features"].to_string(), "cache");
+        assert_eq!(values["database"].to_string(), "postgres");
+        assert_eq!(values["docker"].to_string(), "false");
+    }
+}
@@ -0,0 +1,70 @@
+use std::borrow::Cow;
+
+use include_dir::{Dir, DirEntry, File, include_dir};
+
+use crate::manifest::MANIFEST_FILE;
+use crate::template::{self, TemplateFile};
+
+/// Every project template, compiled into the binary.
+pub static TEMPLATES: Dir<'static> = include_dir!("$CARGO_MANIFEST_DIR/templates");
+
+/// Optional additions (CI, infrastructure) shared by all templates.
+pub static EXTRAS: Dir<'static> = include_dir!("$CARGO_MANIFEST_DIR/extras");
+
+/// Look up an embedded template directory by name.
+pub fn template(name: &str) -> Option<&'static Dir<'static>> {
+    TEMPLATES.get_dir(name)
+}
+
+/// Names of every embedded template, i.e. directories with a manifest.
+pub fn template_names() -> Vec<String> {
+    TEMPLATES
+        .dirs()
+        .filter(|dir| dir.get_file(dir.path().join(MANIFEST_FILE)).is_some())
+        .filter_map(|dir| dir.path().to_str().map(str::to_string))
+        .collect()
+}
+
+/// Look up an embedded extras directory (e.g. `.github`, `terraform`).
+pub fn extra(name: &str) -> Option<&'static Dir<'static>> {
+    EXTRAS.get_dir(name)
+}
+
+/// Every file below an embedded directory, with paths relative to it.
+///
+/// Entry paths inside an embedded `Dir` are relative to the embedding root,
+/// so they are re-based onto `dir` here.
+pub fn template_files(dir: &'static Dir<'static>) -> Vec<TemplateFile> {
+    files(dir)
+        .into_iter()
+        .map(|file| TemplateFile {
+            path: file
+                .path()
+                .strip_prefix(dir.path())
+                .expect("embedded entry outside of its parent directory")
+                .to_path_buf(),
+            contents: Cow::Borrowed(file.contents()),
+        })
+        .collect()
+}
+
+/// Every file below `dir`, depth first, leaving out build artifacts (see
+/// [`template::is_build_artifact`]) that `include_dir!` picks up when a
+/// template was built in place.
+fn files(dir: &'static Dir<'static>) -> Vec<&'static File<'static>> {
+    let mut found = Vec::new();
+    for entry in dir.entries() {
+        if entry
+            .path()
+            .file_name()
+            .is_some_and(template::is_build_artifact)
+        {
+            continue;
+        }
+        match entry {
+            DirEntry::Dir(dir) => found.extend(files(dir)),
+            DirEntry::File(file) => found.push(file),
+        }
+    }
+    found
+}
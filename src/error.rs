@@ -0,0 +1,186 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Everything that can make the CLI fail.
+///
+/// Each variant maps to a stable process exit code (see [`Error::exit_code`])
+/// so scripts can tell a bad invocation from a broken template or a failing
+/// disk.
+#[derive(Debug)]
+pub enum Error {
+    /// The user declined the wizard's summary.
+    Aborted,
+    /// The command line does not parse; only raised with `--json`, clap
+    /// reports it itself otherwise.
+    Usage(String),
+    /// Reading an interactive answer failed (e.g. stdin closed early).
+    Prompt(io::Error),
+    /// The project name breaks Cargo's naming rules.
+    InvalidName(String),
+    /// No template matches the requested name.
+    UnknownTemplate(String),
+    /// A template variable is unknown, missing or invalid.
+    Variable(String),
+    /// The template does not declare support for a requested extra.
+    UnsupportedExtra { template: String, extra: String },
+    /// A template's manifest is missing or malformed.
+    Manifest { template: String, message: String },
+    /// A file or path failed to render.
+    Render { path: PathBuf, message: String },
+    /// `lint-templates` found this many problems.
+    Lint(usize),
+    /// The project `add` or `generate` works on is missing, or the request
+    /// does not fit it (unknown component, bad field, existing file).
+    Project(String),
+    /// A config file is malformed or names an unknown preset.
+    Config(String),
+    /// A `--from` spec does not parse or breaks its schema; one message per
+    /// offending key.
+    Spec { path: PathBuf, errors: Vec<String> },
+    /// The target directory is already there and not empty.
+    DirectoryExists(PathBuf),
+    /// A `git` command run after generation failed.
+    Git { command: String, message: String },
+    /// Reading or writing the filesystem failed.
+    Io { path: PathBuf, source: io::Error },
+}
+
+impl Error {
+    /// Process exit code for this error.
+    ///
+    /// | code | meaning                                   |
+    /// |------|-------------------------------------------|
+    /// | 1    | aborted by the user                       |
+    /// | 2    | invalid input (same as clap usage errors) |
+    /// | 3    | broken template                           |
+    /// | 4    | target directory conflict                 |
+    /// | 5    | filesystem error                          |
+    /// | 6    | post-generation step (git) failed         |
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Aborted => 1,
+            Error::Usage(_)
+            | Error::Prompt(_)
+            | Error::InvalidName(_)
+            | Error::UnknownTemplate(_)
+            | Error::Variable(_)
+            | Error::UnsupportedExtra { .. }
+            | Error::Project(_)
+            | Error::Config(_)
+            | Error::Spec { .. } => 2,
+            Error::Manifest { .. } | Error::Render { .. } | Error::Lint(_) => 3,
+            Error::DirectoryExists(_) => 4,
+            Error::Io { .. } => 5,
+            Error::Git { .. } => 6,
+        }
+    }
+
+    /// Stable identifier of the variant, for `--json` output.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Aborted => "aborted",
+            Error::Usage(_) => "usage",
+            Error::Prompt(_) => "prompt_failed",
+            Error::InvalidName(_) => "invalid_name",
+            Error::UnknownTemplate(_) => "unknown_template",
+            Error::Variable(_) => "invalid_variable",
+            Error::UnsupportedExtra { .. } => "unsupported_extra",
+            Error::Manifest { .. } => "invalid_manifest",
+            Error::Render { .. } => "render_failed",
+            Error::Lint(_) => "lint_failed",
+            Error::Project(_) => "invalid_project",
+            Error::Config(_) => "invalid_config",
+            Error::Spec { .. } => "invalid_spec",
+            Error::DirectoryExists(_) => "directory_exists",
+            Error::Git { .. } => "git_failed",
+            Error::Io { .. } => "io_error",
+        }
+    }
+
+    /// The error as printed by `--json`: its code, exit code and message,
+    /// plus the path it concerns and any per-key spec errors.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut error = serde_json::json!({
+            "code": self.code(),
+            "exit_code": self.exit_code(),
+            "message": self.to_string(),
+        });
+        let path = match self {
+            Error::Spec { path, .. }
+            | Error::Render { path, .. }
+            | Error::DirectoryExists(path)
+            | Error::Io { path, .. } => Some(path),
+            _ => None,
+        };
+        if let Some(path) = path {
+            error["path"] = path.display().to_string().into();
+        }
+        if let Error::Spec { errors, .. } = self {
+            error["details"] = errors.clone().into();
+        }
+        serde_json::json!({ "error": error })
+    }
+
+    /// Attach the path an I/O error happened on.
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Error::Io {
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Aborted => write!(f, "Aborted"),
+            Error::Usage(msg) => write!(f, "{}", msg),
+            Error::Prompt(e) => write!(f, "Could not read an answer: {}", e),
+            Error::InvalidName(msg) => write!(f, "Invalid project name: {}", msg),
+            Error::UnknownTemplate(msg) => write!(f, "{}", msg),
+            Error::Variable(msg) => write!(f, "{}", msg),
+            Error::UnsupportedExtra { template, extra } => write!(
+                f,
+                "Template '{}' does not support the '{}' extra",
+                template, extra
+            ),
+            Error::Manifest { template, message } => {
+                write!(
+                    f,
+                    "Invalid manifest for template '{}': {}",
+                    template, message
+                )
+            }
+            Error::Project(msg) => write!(f, "{}", msg),
+            Error::Config(msg) => write!(f, "{}", msg),
+            Error::Spec { path, errors } => {
+                write!(f, "Invalid spec '{}':", path.display())?;
+                for error in errors {
+                    write!(f, "\n  - {}", error)?;
+                }
+                Ok(())
+            }
+            Error::Render { path, message } => {
+                write!(f, "Failed to render '{}': {}", path.display(), message)
+            }
+            Error::Lint(count) => write!(f, "{} problem(s) found in templates", count),
+            Error::DirectoryExists(path) => write!(
+                f,
+                "Directory '{}' already exists (use --merge or --force to generate into it)",
+                path.display()
+            ),
+            Error::Git { command, message } => write!(f, "'{}' failed: {}", command, message),
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Prompt(e) | Error::Io { source: e, .. } => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,474 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+
+use crate::error::Error;
+use crate::plan::Plan;
+use crate::prompt::Prompter;
+
+/// How to resolve a planned file that already exists with other contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Conflict {
+    /// Keep the existing file
+    Skip,
+    /// Replace the existing file
+    Overwrite,
+    /// Keep the existing file and write the generated one next to it as `<name>.new`
+    New,
+    /// Ask for every conflicting file
+    Prompt,
+}
+
+/// What happened to a single planned file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Created,
+    Overwritten,
+    Skipped,
+    /// Written next to the existing file under this path instead.
+    NewCopy(PathBuf),
+    /// Already present with identical contents.
+    Unchanged,
+    /// An existing file edited in place (see `add`).
+    Updated,
+    /// Merged with conflict markers left for the user to resolve (see
+    /// `upgrade`).
+    Conflict,
+    /// Deleted because the template dropped it (see `upgrade`).
+    Removed,
+}
+
+/// Per-file outcome of a generation run, in plan order.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub entries: Vec<(PathBuf, Outcome)>,
+}
+
+impl Outcome {
+    /// Stable name for machine-readable output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outcome::Created => "created",
+            Outcome::Overwritten => "overwritten",
+            Outcome::Skipped => "skipped",
+            Outcome::NewCopy(_) => "new_copy",
+            Outcome::Unchanged => "unchanged",
+            Outcome::Updated => "updated",
+            Outcome::Conflict => "conflict",
+            Outcome::Removed => "removed",
+        }
+    }
+}
+
+impl Report {
+    pub fn print(&self) {
+        for (path, outcome) in &self.entries {
+            match outcome {
+                Outcome::Created => println!("  created      {}", path.display()),
+                Outcome::Overwritten => println!("  overwritten  {}", path.display()),
+                Outcome::Skipped => println!("  skipped      {}", path.display()),
+                Outcome::NewCopy(copy) => println!("  new copy     {}", copy.display()),
+                Outcome::Unchanged => println!("  unchanged    {}", path.display()),
+                Outcome::Updated => println!("  updated      {}", path.display()),
+                Outcome::Conflict => println!("  conflict     {}", path.display()),
+                Outcome::Removed => println!("  removed      {}", path.display()),
+            }
+        }
+    }
+}
+
+/// Write `plan` to `project_dir`.
+///
+/// A new directory is generated atomically: files are written into a hidden
+/// sibling directory first and only renamed into place once everything
+/// succeeded, so a failure never leaves a half-written project behind.
+///
+/// An existing directory is only written into when it is empty (a bare
+/// `.git` counts as empty) or when `conflict` says how to handle files that
+/// are already there.
+///
+/// `finish` runs on the directory written into once the plan is, before a
+/// new directory is renamed into place, so what it adds (the resources of a
+/// spec) is generated atomically too.
+pub fn generate(
+    plan: &Plan,
+    project_dir: &Path,
+    conflict: Option<Conflict>,
+    prompter: &mut dyn Prompter,
+    finish: &mut dyn FnMut(&Path) -> Result<(), Error>,
+) -> Result<Report, Error> {
+    if !project_dir.exists() {
+        let staging = Staging::create(project_dir)?;
+        plan.write(&staging.path)?;
+        finish(&staging.path)?;
+        staging.commit(project_dir)?;
+        return Ok(Report {
+            entries: plan
+                .files
+                .iter()
+                .map(|file| (file.path.clone(), Outcome::Created))
+                .collect(),
+        });
+    }
+
+    if !project_dir.is_dir() || (conflict.is_none() && !is_empty(project_dir)?) {
+        return Err(Error::DirectoryExists(project_dir.to_path_buf()));
+    }
+    let report = merge(
+        plan,
+        project_dir,
+        conflict.unwrap_or(Conflict::Skip),
+        prompter,
+    )?;
+    finish(project_dir)?;
+    Ok(report)
+}
+
+/// Generate into an existing directory.
+///
+/// Every conflict is resolved before anything is written. If writing then
+/// fails, the files and directories created by this run are removed again;
+/// overwritten files are replaced through a rename so they are never left
+/// truncated.
+fn merge(
+    plan: &Plan,
+    root: &Path,
+    conflict: Conflict,
+    prompter: &mut dyn Prompter,
+) -> Result<Report, Error> {
+    let mut report = Report::default();
+    for file in &plan.files {
+        let dest_path = root.join(&file.path);
+        let outcome = if !dest_path.exists() {
+            Outcome::Created
+        } else if dest_path.is_dir() {
+            Outcome::Skipped
+        } else if fs::read(&dest_path).map_err(|e| Error::io(&dest_path, e))? == file.contents {
+            Outcome::Unchanged
+        } else {
+            resolve(&file.path, conflict, prompter)?
+        };
+        report.entries.push((file.path.clone(), outcome));
+    }
+
+    let mut created = Rollback::default();
+    for ((path, outcome), file) in report.entries.iter().zip(&plan.files) {
+        let target = match outcome {
+            Outcome::Created | Outcome::Overwritten => path.clone(),
+            Outcome::NewCopy(copy) => copy.clone(),
+            Outcome::Skipped
+            | Outcome::Unchanged
+            | Outcome::Updated
+            | Outcome::Conflict
+            | Outcome::Removed => continue,
+        };
+        created.write(
+            root,
+            &target,
+            &file.contents,
+            outcome == &Outcome::Overwritten,
+        )?;
+    }
+    created.keep();
+    Ok(report)
+}
+
+fn resolve(path: &Path, conflict: Conflict, prompter: &mut dyn Prompter) -> Result<Outcome, Error> {
+    let conflict = match conflict {
+        Conflict::Prompt => {
+            let choices = ["skip", "overwrite", "new"].map(String::from);
+            let question = format!("'{}' already exists", path.display());
+            let answer = prompter.select(&question, &choices, "skip")?;
+            Conflict::from_str(&answer, true).expect("answer is one of the choices")
+        }
+        conflict => conflict,
+    };
+    Ok(match conflict {
+        Conflict::Skip | Conflict::Prompt => Outcome::Skipped,
+        Conflict::Overwrite => Outcome::Overwritten,
+        Conflict::New => {
+            let mut name = path.file_name().unwrap_or_default().to_os_string();
+            name.push(".new");
+            Outcome::NewCopy(path.with_file_name(name))
+        }
+    })
+}
+
+/// Whether `dir` holds nothing but possibly a `.git` directory.
+fn is_empty(dir: &Path) -> Result<bool, Error> {
+    for entry in fs::read_dir(dir).map_err(|e| Error::io(dir, e))? {
+        let entry = entry.map_err(|e| Error::io(dir, e))?;
+        if entry.file_name() != ".git" {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Files and directories created while merging, removed on drop unless
+/// [`Rollback::keep`] was called.
+#[derive(Default)]
+struct Rollback {
+    files: Vec<PathBuf>,
+    dirs: BTreeSet<PathBuf>,
+    kept: bool,
+}
+
+impl Rollback {
+    fn write(
+        &mut self,
+        root: &Path,
+        relative: &Path,
+        contents: &[u8],
+        replace: bool,
+    ) -> Result<(), Error> {
+        let dest_path = root.join(relative);
+        for dir in relative.ancestors().skip(1) {
+            let dir = root.join(dir);
+            if dir != root && !dir.exists() {
+                self.dirs.insert(dir);
+            }
+        }
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+        }
+
+        if replace {
+            let mut name = dest_path.file_name().unwrap_or_default().to_os_string();
+            name.push(format!(".tmp-{}", std::process::id()));
+            let tmp = dest_path.with_file_name(name);
+            fs::write(&tmp, contents).map_err(|e| Error::io(&tmp, e))?;
+            fs::rename(&tmp, &dest_path).map_err(|e| {
+                let _ = fs::remove_file(&tmp);
+                Error::io(&dest_path, e)
+            })?;
+        } else {
+            self.files.push(dest_path.clone());
+            fs::write(&dest_path, contents).map_err(|e| Error::io(&dest_path, e))?;
+        }
+        Ok(())
+    }
+
+    fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for Rollback {
+    fn drop(&mut self) {
+        if self.kept {
+            return;
+        }
+        for file in &self.files {
+            let _ = fs::remove_file(file);
+        }
+        // Deepest first, so parents are empty by the time they are removed
+        for dir in self.dirs.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+/// A temporary directory removed on drop unless committed.
+struct Staging {
+    path: PathBuf,
+    committed: bool,
+}
+
+impl Staging {
+    /// Create `.<name>.tmp-<pid>` next to `target`, so the final rename
+    /// stays on one filesystem.
+    fn create(target: &Path) -> Result<Staging, Error> {
+        let parent = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+
+        let name = target
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "project".to_string());
+        let path = parent.join(format!(".{}.tmp-{}", name, std::process::id()));
+        if path.exists() {
+            fs::remove_dir_all(&path).map_err(|e| Error::io(&path, e))?;
+        }
+        fs::create_dir(&path).map_err(|e| Error::io(&path, e))?;
+
+        Ok(Staging {
+            path,
+            committed: false,
+        })
+    }
+
+    fn commit(mut self, target: &Path) -> Result<(), Error> {
+        fs::rename(&self.path, target).map_err(|e| Error::io(target, e))?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::PlannedFile;
+    use crate::prompt::LinePrompter;
+
+    /// An empty directory of its own below the system temp directory.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join("create_woragis-tests")
+            .join(format!("generate-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn plan(files: &[(&str, &str)]) -> Plan {
+        Plan {
+            files: files
+                .iter()
+                .map(|(path, contents)| PlannedFile {
+                    path: PathBuf::from(path),
+                    contents: contents.as_bytes().to_vec(),
+                })
+                .collect(),
+            extras: Vec::new(),
+            conditional: Vec::new(),
+        }
+    }
+
+    fn run(plan: &Plan, dir: &Path, conflict: Option<Conflict>) -> Result<Report, Error> {
+        generate(
+            plan,
+            dir,
+            conflict,
+            &mut LinePrompter::new(&b""[..], Vec::new()),
+            &mut |_| Ok(()),
+        )
+    }
+
+    #[test]
+    fn new_directories_are_generated_whole() {
+        let root = scratch("new");
+        let dir = root.join("demo");
+        let report = run(&plan(&[("src/main.rs", "fn main() {}\n")]), &dir, None).unwrap();
+        assert_eq!(
+            report.entries,
+            [(PathBuf::from("src/main.rs"), Outcome::Created)]
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("src/main.rs")).unwrap(),
+            "fn main() {}\n"
+        );
+        // Nothing of the staging directory is left behind
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 1);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn new_directories_include_what_finish_adds() {
+        let root = scratch("finish");
+        let dir = root.join("demo");
+        let plan = plan(&[("src/main.rs", "fn main() {}\n")]);
+        let mut prompter = LinePrompter::new(&b""[..], Vec::new());
+
+        generate(
+            &plan,
+            &root.join("failed"),
+            None,
+            &mut prompter,
+            &mut |staged| {
+                fs::write(staged.join("src/todo.rs"), "").unwrap();
+                Err(Error::Project("no such generator".to_string()))
+            },
+        )
+        .unwrap_err();
+        // Neither the project nor the staging directory is left behind
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 0);
+
+        generate(&plan, &dir, None, &mut prompter, &mut |staged| {
+            assert!(!dir.exists());
+            fs::write(staged.join("src/todo.rs"), "").map_err(|e| Error::io(staged, e))
+        })
+        .unwrap();
+        assert!(dir.join("src/todo.rs").is_file());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn existing_directories_need_a_conflict_strategy() {
+        let dir = scratch("existing");
+        fs::write(dir.join("README.md"), "ours\n").unwrap();
+        let planned = plan(&[("README.md", "theirs\n"), ("Cargo.toml", "[package]\n")]);
+        assert!(matches!(
+            run(&planned, &dir, None),
+            Err(Error::DirectoryExists(_))
+        ));
+
+        let report = run(&planned, &dir, Some(Conflict::New)).unwrap();
+        assert_eq!(
+            report.entries,
+            [
+                (
+                    PathBuf::from("README.md"),
+                    Outcome::NewCopy(PathBuf::from("README.md.new"))
+                ),
+                (PathBuf::from("Cargo.toml"), Outcome::Created),
+            ]
+        );
+        assert_eq!(fs::read_to_string(dir.join("README.md")).unwrap(), "ours\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("README.md.new")).unwrap(),
+            "theirs\n"
+        );
+
+        let report = run(&planned, &dir, Some(Conflict::Overwrite)).unwrap();
+        assert_eq!(report.entries[0].1, Outcome::Overwritten);
+        assert_eq!(
+            fs::read_to_string(dir.join("README.md")).unwrap(),
+            "theirs\n"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn a_failed_merge_removes_what_it_created() {
+        let dir = scratch("rollback");
+        fs::write(dir.join("README.md"), "ours\n").unwrap();
+        // `blocked` is a file, so nothing can be written below it
+        fs::write(dir.join("blocked"), "").unwrap();
+        let planned = plan(&[
+            ("README.md", "theirs\n"),
+            ("src/new/main.rs", "fn main() {}\n"),
+            ("blocked/file", "never\n"),
+        ]);
+
+        let result = run(&planned, &dir, Some(Conflict::Overwrite));
+        assert!(matches!(result, Err(Error::Io { .. })), "{:?}", result);
+        assert!(!dir.join("src").exists());
+        // Overwrites go through a rename and are not undone, but never left
+        // half-written
+        assert_eq!(
+            fs::read_to_string(dir.join("README.md")).unwrap(),
+            "theirs\n"
+        );
+        let mut left: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["README.md", "blocked"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
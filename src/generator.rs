@@ -0,0 +1,519 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use minijinja::Value;
+use serde::{Deserialize, Serialize};
+use toml_edit::DocumentMut;
+
+use crate::case;
+use crate::error::Error;
+use crate::generate::{Outcome, Report};
+use crate::lock::Lock;
+use crate::manifest::FileRule;
+use crate::name;
+use crate::plan;
+use crate::project::Project;
+use crate::render::{Context, Renderer};
+use crate::template::{Template, TemplateFile};
+use crate::workspace::Workspace;
+
+/// Directory inside a template holding its generators, one per
+/// subdirectory. It is never copied into generated projects.
+pub const GENERATORS_DIR: &str = "generators";
+
+/// The manifest of a generator, next to the files it renders.
+pub const GENERATOR_FILE: &str = "generator.toml";
+
+/// A code generator shipped with a template (`generate <name>`).
+pub struct Generator {
+    pub name: String,
+    pub manifest: GeneratorManifest,
+    /// Files to render, relative to the generated project's root.
+    pub files: Vec<TemplateFile>,
+}
+
+/// `generator.toml`.
+///
+/// ```toml
+/// description = "CRUD resource"
+///
+/// [types.datetime]
+/// rust = "chrono::DateTime<chrono::Utc>"
+/// sql = "{{ 'TEXT' if database == 'sqlite' else 'TIMESTAMPTZ' }}"
+/// features = { sqlx = ["chrono"] }
+///
+/// [[inserts]]
+/// file = "src/models/mod.rs"
+/// line = "pub mod {{ resource }};"
+/// after = ["pub mod ", "use "]
+///
+/// [[files]]
+/// path = "migrations"
+/// when = "database != 'none'"
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GeneratorManifest {
+    pub description: String,
+    /// Field types accepted as `name:type`.
+    #[serde(default)]
+    pub types: BTreeMap<String, FieldType>,
+    /// Lines wired into existing project files.
+    #[serde(default)]
+    pub inserts: Vec<Insert>,
+    /// Files only rendered when a condition holds, as in `template.toml`.
+    #[serde(default)]
+    pub files: Vec<FileRule>,
+}
+
+/// How a field type maps onto Rust and SQL, and the crate features it needs.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldType {
+    pub rust: String,
+    /// Rendered with the project's options, so it can depend on them.
+    pub sql: String,
+    /// Enabled on the dependencies the project has.
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+/// A line added to an existing file, after the last line containing
+/// `after` (or at the end of the file without it).
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Insert {
+    pub file: String,
+    pub line: String,
+    pub after: Option<Anchors>,
+    /// Only insert the line when this condition holds.
+    pub when: Option<String>,
+}
+
+/// One anchor, or several tried in order until one is found.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Anchors {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Anchors {
+    fn all(&self) -> &[String] {
+        match self {
+            Anchors::One(anchor) => std::slice::from_ref(anchor),
+            Anchors::Many(anchors) => anchors,
+        }
+    }
+}
+
+/// A parsed `name:type` field, as templates see it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Field {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The Rust type, wrapped in `Option` when optional.
+    pub rust: String,
+    pub sql: String,
+    pub optional: bool,
+}
+
+/// What `generate` did, plus edits it could not make itself.
+pub struct Generated {
+    pub report: Report,
+    /// `(file, line)` pairs to add by hand.
+    pub manual: Vec<(PathBuf, String)>,
+}
+
+impl Generator {
+    /// Every generator of `template`, sorted by name.
+    pub fn all(template: &Template) -> Result<Vec<Generator>, Error> {
+        let mut files: BTreeMap<String, Vec<TemplateFile>> = BTreeMap::new();
+        for file in &template.files {
+            let Ok(relative) = file.path.strip_prefix(GENERATORS_DIR) else {
+                continue;
+            };
+            let mut parts = relative.iter();
+            let (Some(name), Some(_)) = (parts.next(), parts.clone().next()) else {
+                continue;
+            };
+            files
+                .entry(name.to_string_lossy().into_owned())
+                .or_default()
+                .push(TemplateFile {
+                    path: parts.collect(),
+                    contents: file.contents.clone(),
+                });
+        }
+
+        files
+            .into_iter()
+            .filter_map(|(name, files)| {
+                let (manifest, files): (Vec<_>, Vec<_>) = files
+                    .into_iter()
+                    .partition(|file| file.path == Path::new(GENERATOR_FILE));
+                let manifest = manifest.into_iter().next()?;
+                Some(Generator::parse(template, name, &manifest, files))
+            })
+            .collect()
+    }
+
+    pub fn find(template: &Template, name: &str) -> Result<Generator, Error> {
+        let all = Generator::all(template)?;
+        let names: Vec<&str> = all.iter().map(|g| g.name.as_str()).collect();
+        let available = if names.is_empty() {
+            "none".to_string()
+        } else {
+            names.join(", ")
+        };
+        let error = Error::Project(format!(
+            "Template '{}' has no generator '{}' (available: {})",
+            template.name, name, available
+        ));
+        all.into_iter().find(|g| g.name == name).ok_or(error)
+    }
+
+    fn parse(
+        template: &Template,
+        name: String,
+        manifest: &TemplateFile,
+        files: Vec<TemplateFile>,
+    ) -> Result<Generator, Error> {
+        let manifest = std::str::from_utf8(&manifest.contents)
+            .map_err(|e| e.to_string())
+            .and_then(|source| toml::from_str(source).map_err(|e| e.to_string()))
+            .map_err(|message| Error::Manifest {
+                template: format!("{}/{}/{}", template.name, GENERATORS_DIR, name),
+                message,
+            })?;
+        Ok(Generator {
+            name,
+            manifest,
+            files,
+        })
+    }
+
+    /// Parse `name:type` arguments, where a trailing `?` makes the field
+    /// optional.
+    pub fn parse_fields(&self, raw: &[String]) -> Result<Vec<Field>, Error> {
+        let types: Vec<&str> = self.manifest.types.keys().map(String::as_str).collect();
+        let mut fields: Vec<Field> = Vec::new();
+        for arg in raw {
+            let (name, kind) = arg
+                .split_once(':')
+                .ok_or_else(|| Error::Project(format!("expected NAME:TYPE, got '{}'", arg)))?;
+            let (kind, optional) = match kind.strip_suffix('?') {
+                Some(kind) => (kind, true),
+                None => (kind, false),
+            };
+            let name = identifier(name)?;
+            if name == "id" {
+                return Err(Error::Project(
+                    "'id' is generated for every resource; pick another field name".to_string(),
+                ));
+            }
+            if fields.iter().any(|field| field.name == name) {
+                return Err(Error::Project(format!("field '{}' is given twice", name)));
+            }
+            let field_type = self.manifest.types.get(kind).ok_or_else(|| {
+                Error::Project(format!(
+                    "unknown type '{}' for field '{}' (available: {})",
+                    kind,
+                    name,
+                    types.join(", ")
+                ))
+            })?;
+            fields.push(Field {
+                name,
+                kind: kind.to_string(),
+                rust: if optional {
+                    format!("Option<{}>", field_type.rust)
+                } else {
+                    field_type.rust.clone()
+                },
+                sql: field_type.sql.clone(),
+                optional,
+            });
+        }
+        Ok(fields)
+    }
+}
+
+/// Run `generator` for a new `name` inside `project`.
+///
+/// Nothing is written when one of the files to create already exists.
+/// Inserted lines are skipped when already present, and reported back in
+/// [`Generated::manual`] when their anchor is gone from the file.
+pub fn generate(
+    project: &Project,
+    template: &Template,
+    generator: &Generator,
+    name: &str,
+    fields: &[Field],
+) -> Result<Generated, Error> {
+    let resource = identifier(name)?;
+    let table = case::plural(&resource);
+    let manifest = &template.manifest;
+    let lock = Lock::read(&project.root)?;
+    // In a workspace, files go to the crate the template's layout puts them in
+    let workspace = lock.as_ref().and_then(|lock| lock.project.workspace);
+    let place = |path: &Path| match workspace {
+        Some(_) => Workspace::place(manifest, path),
+        None => path.to_path_buf(),
+    };
+    let migrations = project.root.join(place(Path::new("migrations")));
+    let migration = format!("{:04}_create_{}", next_migration(&migrations)?, table);
+
+    let root = template.root().join(GENERATORS_DIR).join(&generator.name);
+    let render_error = |e: minijinja::Error| Error::Render {
+        path: root.join(GENERATOR_FILE),
+        message: e.to_string(),
+    };
+
+    // The options the project was generated with, where it has a lock
+    let variables = match &lock {
+        Some(lock) => lock.variables(manifest, &[])?,
+        None => manifest.resolve_variables(&[])?,
+    };
+    let (name, author) = match &lock {
+        Some(lock) => (lock.project.name.as_str(), lock.project.author.as_str()),
+        None => (project.name.as_str(), project.author.as_str()),
+    };
+    let mut context = Context::new(name, author);
+    context.set_options(&variables, &[]);
+    context.set_workspace(workspace.as_ref());
+    let mut fields = fields.to_vec();
+    {
+        let renderer = Renderer::new(&context);
+        for field in &mut fields {
+            field.sql = renderer.render_str(&field.sql).map_err(render_error)?;
+        }
+    }
+    context.insert("resource", resource.as_str());
+    context.insert("table", table.as_str());
+    context.insert("migration", migration.as_str());
+    context.insert("fields", Value::from_serialize(&fields));
+    let renderer = Renderer::new(&context);
+
+    let rendered: Vec<_> = plan::render_files(&generator.files, &root, &renderer, |path| {
+        for rule in &generator.manifest.files {
+            if path.starts_with(&rule.path) && !renderer.eval(&rule.when)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    })?
+    .into_iter()
+    .map(|(path, contents)| (place(&path), contents))
+    .collect();
+    if let Some((path, _)) = rendered
+        .iter()
+        .find(|(path, _)| project.root.join(path).exists())
+    {
+        return Err(Error::Project(format!(
+            "'{}' already exists; remove it or pick another name",
+            path.display()
+        )));
+    }
+
+    let mut report = Report::default();
+    let mut manual = Vec::new();
+    let mut edited: BTreeMap<PathBuf, String> = BTreeMap::new();
+    for insert in &generator.manifest.inserts {
+        if let Some(when) = &insert.when
+            && !renderer.eval(when).map_err(render_error)?
+        {
+            continue;
+        }
+        let render = |text: &str| renderer.render_str(text).map_err(render_error);
+        let path = place(Path::new(&render(&insert.file)?));
+        let line = render(&insert.line)?;
+        let after = match &insert.after {
+            Some(anchors) => Some(
+                anchors
+                    .all()
+                    .iter()
+                    .map(|anchor| render(anchor))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+
+        let text = match edited.get(&path) {
+            Some(text) => Some(text.clone()),
+            None => project
+                .read(&path)
+                .and_then(|bytes| String::from_utf8(bytes).ok()),
+        };
+        let Some(text) = text else {
+            manual.push((path, line));
+            continue;
+        };
+        match insert_line(&text, &line, after.as_deref()) {
+            Inserted::Done(text) => {
+                edited.insert(path, text);
+            }
+            Inserted::Present => {}
+            Inserted::NoAnchor => manual.push((path, line)),
+        }
+    }
+
+    // A workspace declares the dependencies once for every crate
+    let cargo_path = PathBuf::from("Cargo.toml");
+    let section: &[&str] = match workspace {
+        Some(_) => &["workspace", "dependencies"],
+        None => &["dependencies"],
+    };
+    let mut features: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for field in &fields {
+        for (krate, wanted) in &generator.manifest.types[&field.kind].features {
+            features
+                .entry(krate)
+                .or_default()
+                .extend(wanted.iter().map(String::as_str));
+        }
+    }
+    if !features.is_empty()
+        && let Some(mut cargo) = project
+            .read(&cargo_path)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .and_then(|text| text.parse::<DocumentMut>().ok())
+    {
+        let mut changed = false;
+        for (krate, wanted) in features {
+            let dependencies = section
+                .iter()
+                .try_fold(cargo.as_item(), |item, key| item.get(key))
+                .and_then(|item| item.as_table_like());
+            if !dependencies.is_some_and(|dependencies| dependencies.contains_key(krate)) {
+                continue;
+            }
+            match enable_features(&mut cargo, section, krate, &wanted) {
+                Some(added) => changed |= added,
+                None => manual.push((
+                    cargo_path.clone(),
+                    format!("{} = {{ features = {:?} }}", krate, wanted),
+                )),
+            }
+        }
+        if changed {
+            edited.insert(cargo_path, cargo.to_string());
+        }
+    }
+
+    let mut writes = Vec::new();
+    for (path, contents) in rendered {
+        report.entries.push((path.clone(), Outcome::Created));
+        writes.push((path, contents));
+    }
+    for (path, text) in edited {
+        report.entries.push((path.clone(), Outcome::Updated));
+        writes.push((path, text.into_bytes()));
+    }
+    project.write(writes)?;
+    Ok(Generated { report, manual })
+}
+
+enum Inserted {
+    Done(String),
+    Present,
+    NoAnchor,
+}
+
+/// Insert `line` after the last line containing the first of `anchors`
+/// found in `text`.
+fn insert_line(text: &str, line: &str, anchors: Option<&[String]>) -> Inserted {
+    if text.lines().any(|l| l.trim() == line.trim()) {
+        return Inserted::Present;
+    }
+    let mut lines: Vec<&str> = text.lines().collect();
+    let at = match anchors {
+        Some(anchors) => {
+            let found = anchors
+                .iter()
+                .find_map(|anchor| lines.iter().rposition(|l| l.contains(anchor.as_str())));
+            match found {
+                Some(i) => i + 1,
+                None => return Inserted::NoAnchor,
+            }
+        }
+        None => lines.len(),
+    };
+    lines.insert(at, line);
+    Inserted::Done(lines.join("\n") + "\n")
+}
+
+/// Turn on `features` of the dependency `krate` in the `section` table,
+/// turning a plain version string into an inline table if needed. Returns
+/// whether anything changed, or `None` when the dependency is not a version
+/// or table.
+fn enable_features(
+    cargo: &mut DocumentMut,
+    section: &[&str],
+    krate: &str,
+    features: &[&str],
+) -> Option<bool> {
+    let dependencies = section
+        .iter()
+        .try_fold(cargo.as_item_mut(), |item, key| item.get_mut(key))?;
+    let dependency = dependencies.get_mut(krate)?;
+    if let Some(version) = dependency.as_str() {
+        let mut table = toml_edit::InlineTable::new();
+        table.insert("version", version.into());
+        *dependency = toml_edit::value(table);
+    }
+    let table = dependency.as_table_like_mut()?;
+    let list = table
+        .entry("features")
+        .or_insert(toml_edit::value(toml_edit::Array::new()))
+        .as_array_mut()?;
+
+    let mut changed = false;
+    for feature in features {
+        if !list.iter().any(|value| value.as_str() == Some(feature)) {
+            list.push(*feature);
+            changed = true;
+        }
+    }
+    Some(changed)
+}
+
+/// The next free migration number in the `dir` of migrations.
+fn next_migration(dir: &Path) -> Result<u32, Error> {
+    if !dir.is_dir() {
+        return Ok(1);
+    }
+    let mut last = 0;
+    for entry in fs::read_dir(dir).map_err(|e| Error::io(dir, e))? {
+        let entry = entry.map_err(|e| Error::io(dir, e))?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let digits: String = name.chars().take_while(|c| c.is_ascii_digit()).collect();
+        if let Ok(number) = digits.parse::<u32>() {
+            last = last.max(number);
+        }
+    }
+    Ok(last + 1)
+}
+
+/// `raw` as a snake_case Rust identifier.
+fn identifier(raw: &str) -> Result<String, Error> {
+    let ident = case::snake_case(raw);
+    if ident.is_empty() || !ident.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(Error::Project(format!(
+            "'{}' is not a valid identifier (start with a letter)",
+            raw
+        )));
+    }
+    if !ident.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(Error::Project(format!(
+            "'{}' is not a valid identifier (use ASCII letters, digits and '_')",
+            raw
+        )));
+    }
+    if name::is_keyword(&ident) {
+        return Err(Error::Project(format!("'{}' is a Rust keyword", raw)));
+    }
+    Ok(ident)
+}
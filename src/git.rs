@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::Error;
+use crate::manifest::{Extra, VarValue};
+use crate::template::{Origin, Template};
+use crate::workspace::Workspace;
+
+/// Patterns every generated project ignores.
+pub const BASE_IGNORES: &[&str] = &["target/"];
+
+/// The message of the first commit, recording how the project was generated.
+pub fn scaffold_message(
+    template: &Template,
+    variables: &BTreeMap<String, VarValue>,
+    extras: &[Extra],
+    workspace: Option<&Workspace>,
+) -> String {
+    let mut message = String::from("Initial scaffold\n\n");
+    let origin = match template.origin {
+        Origin::Builtin => String::new(),
+        ref origin => format!(" ({})", origin),
+    };
+    message.push_str(&format!(
+        "Generated by create_woragis_api {} from the '{}' template{}.\n",
+        env!("CARGO_PKG_VERSION"),
+        template.name,
+        origin
+    ));
+    if !variables.is_empty() {
+        message.push_str("\nOptions:\n");
+        for (key, value) in variables {
+            message.push_str(&format!("  {} = {}\n", key, value));
+        }
+    }
+    let extras: Vec<String> = extras.iter().map(Extra::to_string).collect();
+    if let Some(workspace) = workspace {
+        let members: Vec<String> = workspace
+            .members()
+            .iter()
+            .map(|member| member.display().to_string())
+            .collect();
+        message.push_str(&format!("\nWorkspace: {}\n", members.join(", ")));
+    }
+    message.push_str(&format!(
+        "\nExtras: {}\n",
+        if extras.is_empty() {
+            "none".to_string()
+        } else {
+            extras.join(", ")
+        }
+    ));
+    message
+}
+
+/// Make `dir` a git repository (unless it already is inside one) and commit
+/// everything below it as `author`.
+///
+/// Only `dir` is staged and committed, so generating into a subdirectory of
+/// an existing repository leaves the rest of its index alone. The identity
+/// is given on the command line, so this works where git has none
+/// configured, e.g. in CI or a fresh container.
+pub fn init_and_commit(dir: &Path, message: &str, author: &str) -> Result<(), Error> {
+    if !is_inside_work_tree(dir) {
+        run(dir, &["init", "--quiet"])?;
+    }
+    run(dir, &["add", "--all", "--", "."])?;
+    // `Jane Doe <jane@example.com>`, or a bare name with the configured email
+    let (name, email) = match author.split_once(" <") {
+        Some((name, email)) => (name.trim(), email.trim_end_matches('>').to_string()),
+        None => (author.trim(), config(dir, "user.email").unwrap_or_default()),
+    };
+    let name = format!("user.name={}", name);
+    let email = format!("user.email={}", email);
+    run(
+        dir,
+        &[
+            "-c",
+            &name,
+            "-c",
+            &email,
+            "commit",
+            "--quiet",
+            "--message",
+            message,
+            "--",
+            ".",
+        ],
+    )?;
+    Ok(())
+}
+
+/// A value of the git configuration as seen from `dir`, `None` when unset
+/// (or without git).
+pub fn config(dir: &Path, key: &str) -> Option<String> {
+    let value = run(dir, &["config", "--get", key]).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// `Name <email>` from `user.name` and `user.email`.
+pub fn author(dir: &Path) -> Option<String> {
+    let name = config(dir, "user.name")?;
+    Some(match config(dir, "user.email") {
+        Some(email) => format!("{} <{}>", name, email),
+        None => name,
+    })
+}
+
+/// Where the project named `name` will be hosted: the `origin` of the
+/// repository `dir` is in, or `https://github.com/<github.user>/<name>`.
+pub fn repository(dir: &Path, name: &str) -> Option<String> {
+    if let Some(url) = config(dir, "remote.origin.url") {
+        return Some(web_url(&url));
+    }
+    config(dir, "github.user").map(|user| format!("https://github.com/{}/{}", user, name))
+}
+
+/// The browsable form of a remote URL: `git@github.com:acme/api.git` is
+/// `https://github.com/acme/api`.
+fn web_url(url: &str) -> String {
+    let url = url.trim_end_matches('/').trim_end_matches(".git");
+    if let Some((host, path)) = url
+        .strip_prefix("git@")
+        .and_then(|rest| rest.split_once(':'))
+    {
+        return format!("https://{}/{}", host, path);
+    }
+    match url.strip_prefix("ssh://") {
+        Some(rest) => format!(
+            "https://{}",
+            rest.split_once('@').map_or(rest, |(_, host)| host)
+        ),
+        None => url.to_string(),
+    }
+}
+
+fn is_inside_work_tree(dir: &Path) -> bool {
+    run(dir, &["rev-parse", "--is-inside-work-tree"])
+        .map(|out| out.trim() == "true")
+        .unwrap_or(false)
+}
+
+/// Run `git` in `dir`, returning its stdout.
+pub fn run(dir: &Path, args: &[&str]) -> Result<String, Error> {
+    let command = format!("git {}", args.join(" "));
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| Error::Git {
+            command: command.clone(),
+            message: e.to_string(),
+        })?;
+
+    if !output.status.success() {
+        return Err(Error::Git {
+            command,
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,248 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+
+use crate::plan::Plan;
+
+const MIT: &str = include_str!("../licenses/MIT.txt");
+const APACHE: &str = include_str!("../licenses/Apache-2.0.txt");
+const PROPRIETARY: &str = include_str!("../licenses/proprietary.txt");
+
+/// The license a generated project is published under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum License {
+    #[value(name = "MIT")]
+    Mit,
+    #[value(name = "Apache-2.0")]
+    Apache,
+    /// MIT or Apache-2.0 at the user's option, as most Rust crates
+    Dual,
+    /// All rights reserved; the crate is not published
+    Proprietary,
+}
+
+impl License {
+    /// Parse a license as written in a config or spec, ignoring case.
+    pub fn parse(raw: &str) -> Result<License, String> {
+        License::from_str(raw, true).map_err(|_| {
+            let names: Vec<String> = License::value_variants()
+                .iter()
+                .filter_map(|license| license.to_possible_value())
+                .map(|value| value.get_name().to_string())
+                .collect();
+            format!(
+                "unknown license '{}' (expected one of: {})",
+                raw,
+                names.join(", ")
+            )
+        })
+    }
+
+    /// The SPDX expression for Cargo's `license`, `None` when proprietary.
+    pub fn expression(self) -> Option<&'static str> {
+        match self {
+            License::Mit => Some("MIT"),
+            License::Apache => Some("Apache-2.0"),
+            License::Dual => Some("MIT OR Apache-2.0"),
+            License::Proprietary => None,
+        }
+    }
+
+    /// The license files, with `holder` and the current year filled in.
+    pub fn files(self, holder: &str) -> Vec<(&'static str, String)> {
+        let fill = |text: &str| {
+            text.replace("{year}", &current_year().to_string())
+                .replace("{holder}", holder)
+        };
+        match self {
+            License::Mit => vec![("LICENSE", fill(MIT))],
+            License::Apache => vec![("LICENSE", APACHE.to_string())],
+            License::Dual => vec![
+                ("LICENSE-APACHE", APACHE.to_string()),
+                ("LICENSE-MIT", fill(MIT)),
+            ],
+            License::Proprietary => vec![("LICENSE", fill(PROPRIETARY))],
+        }
+    }
+
+    /// Add the license files to `plan`, declare the license in every
+    /// `Cargo.toml` and mention it at the end of the README.
+    pub fn apply(self, plan: &mut Plan, author: &str) {
+        // `Jane Doe <jane@example.com>` holds the copyright as `Jane Doe`
+        let holder = author.split(" <").next().unwrap_or(author).trim();
+        let files = self.files(holder);
+        match self.expression() {
+            Some(expression) => plan.set_package("license", |_| expression),
+            None => {
+                // Relative to each manifest, e.g. `../../LICENSE` in crates/api
+                plan.set_package("license-file", |dir| {
+                    "../".repeat(dir.components().count()) + "LICENSE"
+                });
+                plan.set_package("publish", |_| false);
+            }
+        }
+
+        let names: Vec<String> = files
+            .iter()
+            .map(|(path, _)| format!("[{0}]({0})", path))
+            .collect();
+        let section = match self {
+            License::Mit | License::Apache => format!(
+                "## License\n\nLicensed under the {} license; see {}.\n",
+                self.expression().unwrap_or_default(),
+                names.join(" and ")
+            ),
+            License::Dual => format!(
+                "## License\n\nLicensed under either of the Apache License, Version 2.0 or the MIT license, at your option; see {}.\n",
+                names.join(" and ")
+            ),
+            License::Proprietary => format!(
+                "## License\n\nProprietary; all rights reserved. See {}.\n",
+                names.join(" and ")
+            ),
+        };
+        plan.append("README.md", &section);
+        for (path, contents) in files {
+            plan.add(path, contents);
+        }
+    }
+}
+
+/// The current year in UTC.
+fn current_year() -> i64 {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    // Days to a civil date, after Howard Hinnant's `civil_from_days`
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let year = year_of_era + era * 400;
+    if month >= 10 { year + 1 } else { year }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use toml_edit::DocumentMut;
+
+    use super::*;
+    use crate::plan::PlannedFile;
+
+    /// A workspace-shaped plan: a root and a member manifest and a README.
+    fn plan() -> Plan {
+        let files = [
+            ("Cargo.toml", "[package]\nname = \"demo\"\n"),
+            ("crates/api/Cargo.toml", "[package]\nname = \"api\"\n"),
+            ("README.md", "# demo\n"),
+        ];
+        Plan {
+            files: files
+                .iter()
+                .map(|(path, contents)| PlannedFile {
+                    path: PathBuf::from(path),
+                    contents: contents.as_bytes().to_vec(),
+                })
+                .collect(),
+            extras: Vec::new(),
+            conditional: Vec::new(),
+        }
+    }
+
+    fn text<'a>(plan: &'a Plan, path: &str) -> &'a str {
+        let file = plan
+            .files
+            .iter()
+            .find(|file| file.path == Path::new(path))
+            .unwrap_or_else(|| panic!("no {}", path));
+        std::str::from_utf8(&file.contents).unwrap()
+    }
+
+    fn package(plan: &Plan, path: &str) -> toml_edit::Table {
+        let cargo: DocumentMut = text(plan, path).parse().unwrap();
+        cargo["package"].as_table().unwrap().clone()
+    }
+
+    #[test]
+    fn licenses_parse_in_any_case() {
+        assert_eq!(License::parse("mit"), Ok(License::Mit));
+        assert_eq!(License::parse("APACHE-2.0"), Ok(License::Apache));
+        assert_eq!(License::parse("Dual"), Ok(License::Dual));
+        let error = License::parse("GPL").unwrap_err();
+        assert!(
+            error.contains("MIT, Apache-2.0, dual, proprietary"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn mit_names_the_holder_without_their_email() {
+        let mut plan = plan();
+        License::Mit.apply(&mut plan, "Jane Doe <jane@example.com>");
+
+        let license = text(&plan, "LICENSE");
+        let year = current_year();
+        assert!(
+            license.contains(&format!("Copyright (c) {} Jane Doe\n", year)),
+            "{}",
+            license
+        );
+        for path in ["Cargo.toml", "crates/api/Cargo.toml"] {
+            let package = package(&plan, path);
+            assert_eq!(package["license"].as_str(), Some("MIT"), "{}", path);
+            assert!(package.get("license-file").is_none());
+        }
+        assert_eq!(
+            text(&plan, "README.md"),
+            "# demo\n\n## License\n\nLicensed under the MIT license; see [LICENSE](LICENSE).\n"
+        );
+    }
+
+    #[test]
+    fn dual_adds_both_files() {
+        let mut plan = plan();
+        License::Dual.apply(&mut plan, "Jane Doe");
+
+        assert!(text(&plan, "LICENSE-APACHE").contains("Apache License"));
+        assert!(text(&plan, "LICENSE-MIT").contains("Jane Doe"));
+        assert!(
+            !plan
+                .files
+                .iter()
+                .any(|file| file.path == Path::new("LICENSE"))
+        );
+        assert_eq!(
+            package(&plan, "Cargo.toml")["license"].as_str(),
+            Some("MIT OR Apache-2.0")
+        );
+        assert!(
+            text(&plan, "README.md")
+                .contains("see [LICENSE-APACHE](LICENSE-APACHE) and [LICENSE-MIT](LICENSE-MIT).")
+        );
+    }
+
+    #[test]
+    fn proprietary_points_every_manifest_at_the_root_license() {
+        let mut plan = plan();
+        License::Proprietary.apply(&mut plan, "Acme <legal@acme.test>");
+
+        assert!(text(&plan, "LICENSE").contains("Acme. All rights reserved."));
+        for (path, file) in [
+            ("Cargo.toml", "LICENSE"),
+            ("crates/api/Cargo.toml", "../../LICENSE"),
+        ] {
+            let package = package(&plan, path);
+            assert!(package.get("license").is_none(), "{}", path);
+            assert_eq!(package["license-file"].as_str(), Some(file), "{}", path);
+            assert_eq!(package["publish"].as_bool(), Some(false), "{}", path);
+        }
+        assert!(text(&plan, "README.md").contains("Proprietary; all rights reserved."));
+    }
+}
@@ -0,0 +1,788 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use syn::visit::{self, Visit};
+
+use crate::error::Error;
+use crate::manifest::{Manifest, VarKind, Variable};
+use crate::plan::Plan;
+use crate::render::{Context, Renderer};
+use crate::template::Template;
+use crate::workspace::Workspace;
+
+/// Project name templates are rendered with while linting.
+const LINT_PROJECT: &str = "lint_check";
+
+/// Crates every Rust program can `use` without declaring them.
+const BUILTIN_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+
+/// Primitive types, whose associated items paths such as `u64::MAX` name.
+const PRIMITIVES: &[&str] = &[
+    "bool", "char", "str", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16",
+    "u32", "u64", "u128", "usize",
+];
+
+/// Tools whose attributes, like `#[rustfmt::skip]`, are not crates.
+const TOOLS: &[&str] = &["clippy", "diagnostic", "rustfmt"];
+
+/// What `lint-templates` found in one template.
+#[derive(Debug)]
+pub struct Linted {
+    /// Labels of the variable combinations rendered, e.g.
+    /// `framework=axum database=sqlite features=auth docker=true`.
+    pub combinations: Vec<String>,
+    pub problems: Vec<Problem>,
+}
+
+/// One problem, merged across every combination that has it.
+#[derive(Debug)]
+pub struct Problem {
+    pub file: PathBuf,
+    /// The line in the first combination that has the problem; rendering
+    /// shifts it in the others.
+    pub line: Option<usize>,
+    pub message: String,
+    /// Labels of the combinations with the problem.
+    pub combinations: Vec<String>,
+}
+
+/// A problem in a single rendering.
+struct Finding {
+    file: PathBuf,
+    line: Option<usize>,
+    message: String,
+}
+
+impl Finding {
+    fn new(file: &Path, line: Option<usize>, message: impl Into<String>) -> Finding {
+        Finding {
+            file: file.to_path_buf(),
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+/// `lint-templates`: render `template` with every combination of its
+/// variables and check the Rust crates it produces without compiling them.
+///
+/// Every file must parse, every `mod` must have a file and every file under
+/// `src/` a `mod`, `crate::…` (and `self::`/`super::`) paths, in `use` items
+/// and in code, must name modules and items that exist, and the other crates
+/// they name must be in the crate's `Cargo.toml`.
+pub fn lint(template: &Template) -> Result<Linted, Error> {
+    let manifest = &template.manifest;
+    let mut linted = Linted {
+        combinations: Vec::new(),
+        problems: Vec::new(),
+    };
+    // A template that supports it is also linted as a workspace, with the
+    // worker so every crate is checked
+    let mut layouts = vec![None];
+    if Workspace::supports(template) {
+        layouts.push(Some(Workspace { worker: true }));
+    }
+    for (combination, workspace) in combinations(manifest).into_iter().flat_map(|combination| {
+        layouts
+            .iter()
+            .map(move |layout| (combination.clone(), *layout))
+    }) {
+        let mut label = label(manifest, &combination);
+        if workspace.is_some() {
+            label.push_str(" (workspace)");
+        }
+        let variables = manifest.resolve_variables(&combination)?;
+        let mut context = Context::new(LINT_PROJECT, "lint");
+        context.set_options(&variables, &[]);
+        context.set_workspace(workspace.as_ref());
+        let renderer = Renderer::new(&context);
+        let findings = match Plan::build(template, &[], workspace.as_ref(), &renderer) {
+            Ok(plan) => check(&plan),
+            Err(Error::Render { path, message }) => vec![Finding::new(&path, None, message)],
+            Err(e) => return Err(e),
+        };
+
+        for finding in findings {
+            match linted
+                .problems
+                .iter_mut()
+                .find(|p| p.file == finding.file && p.message == finding.message)
+            {
+                // A file can name the same missing path on several lines
+                Some(problem) if problem.combinations.last() == Some(&label) => {}
+                Some(problem) => problem.combinations.push(label.clone()),
+                None => linted.problems.push(Problem {
+                    file: finding.file,
+                    line: finding.line,
+                    message: finding.message,
+                    combinations: vec![label.clone()],
+                }),
+            }
+        }
+        linted.combinations.push(label);
+    }
+    linted
+        .problems
+        .sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    Ok(linted)
+}
+
+/// Every combination of values worth rendering, as `KEY=VALUE` overrides:
+/// each choice of a variable with choices, both values of a bool and every
+/// subset of a list that already holds what its elements require. Free-form
+/// strings keep their defaults.
+fn combinations(manifest: &Manifest) -> Vec<Vec<(String, String)>> {
+    let mut combinations = vec![Vec::new()];
+    for variable in &manifest.variables {
+        let values = values(variable);
+        if values.is_empty() {
+            continue;
+        }
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination: Vec<(String, String)>| {
+                values.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.push((variable.name.clone(), value.clone()));
+                    combination
+                })
+            })
+            .collect();
+    }
+    combinations
+}
+
+fn values(variable: &Variable) -> Vec<String> {
+    match variable.kind {
+        VarKind::Bool => vec!["true".to_string(), "false".to_string()],
+        VarKind::List => {
+            let choices = &variable.choices;
+            (0..1usize << choices.len())
+                .map(|mask| {
+                    (0..choices.len())
+                        .filter(|i| mask & (1 << i) != 0)
+                        .map(|i| &choices[i])
+                        .collect::<Vec<_>>()
+                })
+                .filter(|items| {
+                    items.iter().all(|item| {
+                        variable
+                            .requires
+                            .get(*item)
+                            .into_iter()
+                            .flatten()
+                            .all(|required| items.contains(&required))
+                    })
+                })
+                .map(|items| {
+                    items
+                        .iter()
+                        .map(|item| item.as_str())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+                .collect()
+        }
+        VarKind::String if !variable.choices.is_empty() => variable.choices.clone(),
+        VarKind::String if variable.default.is_none() => vec![LINT_PROJECT.to_string()],
+        VarKind::String => Vec::new(),
+    }
+}
+
+/// `framework=axum features=auth,cache`, leaving out variables that only
+/// take one value.
+fn label(manifest: &Manifest, combination: &[(String, String)]) -> String {
+    let varying: BTreeSet<&str> = manifest
+        .variables
+        .iter()
+        .filter(|variable| values(variable).len() > 1)
+        .map(|variable| variable.name.as_str())
+        .collect();
+    let parts: Vec<String> = combination
+        .iter()
+        .filter(|(key, _)| varying.contains(key.as_str()))
+        .map(|(key, value)| {
+            if value.is_empty() {
+                format!("{}=(none)", key)
+            } else {
+                format!("{}={}", key, value)
+            }
+        })
+        .collect();
+    if parts.is_empty() {
+        "defaults".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Check every crate (every `Cargo.toml` with a `src/main.rs`, `src/lib.rs`
+/// or `src/bin/*.rs`) in `plan`.
+fn check(plan: &Plan) -> Vec<Finding> {
+    let files: BTreeMap<&Path, &[u8]> = plan
+        .files
+        .iter()
+        .map(|file| (file.path.as_path(), file.contents.as_slice()))
+        .collect();
+    let mut findings = Vec::new();
+    for (path, contents) in &files {
+        if path.file_name().is_some_and(|name| name == "Cargo.toml") {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            let mut krate = Crate {
+                files: &files,
+                manifest: path,
+                modules: Vec::new(),
+                loaded: BTreeSet::new(),
+                unparsed: Vec::new(),
+                findings: Vec::new(),
+            };
+            krate.check(dir, contents);
+            findings.extend(krate.findings);
+        }
+    }
+    findings
+}
+
+/// A module of the crate being checked.
+struct Module {
+    /// `crate::models::user`, for messages.
+    name: String,
+    file: PathBuf,
+    /// Where the files of its `mod foo;` children live.
+    dir: PathBuf,
+    parent: Option<usize>,
+    children: BTreeMap<String, usize>,
+    /// Every other name it defines or imports.
+    items: BTreeSet<String>,
+    /// Whether names can come from somewhere this check cannot see: a glob
+    /// import or a macro call such as `include_proto!`.
+    open: bool,
+    references: Vec<Reference>,
+}
+
+/// A path the module names: one of a `use` item, with groups flattened, or
+/// one of two or more segments in its code, like `sqlx::Error`.
+struct Reference {
+    segments: Vec<String>,
+    /// `::name`, always an external crate.
+    absolute: bool,
+    /// From a `use` item rather than from code.
+    import: bool,
+    line: usize,
+}
+
+struct Crate<'a> {
+    files: &'a BTreeMap<&'a Path, &'a [u8]>,
+    manifest: &'a Path,
+    modules: Vec<Module>,
+    loaded: BTreeSet<PathBuf>,
+    /// Directories of modules that do not parse.
+    unparsed: Vec<PathBuf>,
+    findings: Vec<Finding>,
+}
+
+impl Crate<'_> {
+    fn check(&mut self, dir: &Path, cargo_toml: &[u8]) {
+        let manifest: toml::Table = match std::str::from_utf8(cargo_toml)
+            .map_err(|e| e.to_string())
+            .and_then(|source| toml::from_str(source).map_err(|e| e.to_string()))
+        {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                self.findings.push(Finding::new(self.manifest, None, e));
+                return;
+            }
+        };
+        let mut crates = dependencies(&manifest);
+        crates.extend(BUILTIN_CRATES.iter().map(|name| name.to_string()));
+
+        let src = dir.join("src");
+        let lib = src.join("lib.rs");
+        if self.files.contains_key(lib.as_path())
+            && let Some(name) = manifest
+                .get("package")
+                .and_then(|package| package.get("name"))
+                .and_then(|name| name.as_str())
+        {
+            // Binaries use the library by the package's name
+            crates.insert(name.replace('-', "_"));
+        }
+        let bins = src.join("bin");
+        let roots: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| {
+                **path == src.join("main.rs")
+                    || **path == lib
+                    || (path.parent() == Some(&bins)
+                        && path.extension().is_some_and(|ext| ext == "rs"))
+            })
+            .map(|path| path.to_path_buf())
+            .collect();
+        if roots.is_empty() {
+            return;
+        }
+
+        for root in &roots {
+            let start = self.modules.len();
+            let dir = root.parent().unwrap_or(&src).to_path_buf();
+            self.load(root, "crate".to_string(), &dir, None);
+            for index in start..self.modules.len() {
+                self.resolve(index, start, &crates);
+            }
+        }
+
+        for path in self.files.keys() {
+            if path.starts_with(&src)
+                && path.extension().is_some_and(|ext| ext == "rs")
+                && !self.loaded.contains(*path)
+                && !self.unparsed.iter().any(|dir| path.starts_with(dir))
+            {
+                self.findings.push(Finding::new(
+                    path,
+                    None,
+                    "not declared with `mod` in any module of the crate",
+                ));
+            }
+        }
+    }
+
+    /// Parse `file` as the module `name` and load its `mod foo;` children.
+    fn load(&mut self, file: &Path, name: String, dir: &Path, parent: Option<usize>) -> usize {
+        self.loaded.insert(file.to_path_buf());
+        let index = self.new_module(name, file, dir, parent);
+        let source = String::from_utf8_lossy(self.files[file]);
+        match syn::parse_file(&source) {
+            Ok(parsed) => self.items(index, &parsed.items),
+            Err(e) => {
+                self.findings.push(Finding::new(
+                    file,
+                    Some(e.span().start().line),
+                    format!("does not parse: {}", e),
+                ));
+                // Its children and items are unknown, so report nothing about them
+                self.modules[index].open = true;
+                self.unparsed.push(dir.to_path_buf());
+            }
+        }
+        index
+    }
+
+    fn new_module(
+        &mut self,
+        name: String,
+        file: &Path,
+        dir: &Path,
+        parent: Option<usize>,
+    ) -> usize {
+        self.modules.push(Module {
+            name,
+            file: file.to_path_buf(),
+            dir: dir.to_path_buf(),
+            parent,
+            children: BTreeMap::new(),
+            items: BTreeSet::new(),
+            open: false,
+            references: Vec::new(),
+        });
+        self.modules.len() - 1
+    }
+
+    fn items(&mut self, index: usize, items: &[syn::Item]) {
+        for item in items {
+            if let syn::Item::Mod(module) = item {
+                self.module(index, module);
+                continue;
+            }
+            self.modules[index].visit_item(item);
+            let ident = match item {
+                syn::Item::Macro(item) => match &item.ident {
+                    Some(ident) => ident,
+                    None => {
+                        self.modules[index].open = true;
+                        continue;
+                    }
+                },
+                syn::Item::Fn(item) => &item.sig.ident,
+                syn::Item::Struct(item) => &item.ident,
+                syn::Item::Enum(item) => &item.ident,
+                syn::Item::Union(item) => &item.ident,
+                syn::Item::Trait(item) => &item.ident,
+                syn::Item::TraitAlias(item) => &item.ident,
+                syn::Item::Type(item) => &item.ident,
+                syn::Item::Const(item) => &item.ident,
+                syn::Item::Static(item) => &item.ident,
+                // `use` and `extern crate` items name theirs as they are visited
+                _ => continue,
+            };
+            self.modules[index].items.insert(ident.to_string());
+        }
+    }
+
+    /// A `mod` item: inline, or loaded from `name.rs` or `name/mod.rs`.
+    fn module(&mut self, parent: usize, item: &syn::ItemMod) {
+        let name = item.ident.to_string();
+        let full_name = format!("{}::{}", self.modules[parent].name, name);
+        let dir = self.modules[parent].dir.join(&name);
+        let child = match &item.content {
+            Some((_, items)) => {
+                let file = self.modules[parent].file.clone();
+                let child = self.new_module(full_name, &file, &dir, Some(parent));
+                self.items(child, items);
+                child
+            }
+            None => {
+                let flat = self.modules[parent].dir.join(format!("{}.rs", name));
+                let nested = dir.join("mod.rs");
+                if self.files.contains_key(flat.as_path()) {
+                    self.load(&flat, full_name, &dir, Some(parent))
+                } else if self.files.contains_key(nested.as_path()) {
+                    self.load(&nested, full_name, &dir, Some(parent))
+                } else {
+                    let file = self.modules[parent].file.clone();
+                    self.findings.push(Finding::new(
+                        &file,
+                        Some(item.mod_token.span.start().line),
+                        format!(
+                            "`mod {};` has neither {} nor {}",
+                            name,
+                            flat.display(),
+                            nested.display()
+                        ),
+                    ));
+                    // Keep resolving paths through it quiet
+                    let child = self.new_module(full_name, &file, &dir, Some(parent));
+                    self.modules[child].open = true;
+                    child
+                }
+            }
+        };
+        self.modules[parent].children.insert(name, child);
+    }
+
+    /// Check the paths module `index` names; `root` is its crate root.
+    fn resolve(&mut self, index: usize, root: usize, crates: &BTreeSet<String>) {
+        let module = &self.modules[index];
+        let mut findings = Vec::new();
+        for reference in &module.references {
+            let segments = match reference.segments.split_last() {
+                Some((last, init)) if last == "self" && !init.is_empty() => init,
+                _ => &reference.segments[..],
+            };
+            let path = match reference.import {
+                true => format!("use {}", segments.join("::")),
+                false => segments.join("::"),
+            };
+            let first = reference.segments[0].as_str();
+            let (start, rest) = match first {
+                "crate" => (Some(root), &reference.segments[1..]),
+                "self" | "super" => (Some(index), &reference.segments[..]),
+                _ if !reference.absolute && module.children.contains_key(first) => {
+                    (Some(index), &reference.segments[..])
+                }
+                // An enum, type or re-export imported into this module
+                _ if !reference.absolute && (module.items.contains(first) || module.open) => {
+                    (None, &reference.segments[..])
+                }
+                _ => {
+                    if !crates.contains(first) {
+                        findings.push(Finding::new(
+                            &module.file,
+                            Some(reference.line),
+                            format!(
+                                "`{}`: crate `{}` is not a dependency in {}",
+                                path,
+                                first,
+                                self.manifest.display()
+                            ),
+                        ));
+                    }
+                    (None, &reference.segments[..])
+                }
+            };
+            let Some(mut current) = start else {
+                continue;
+            };
+            for segment in rest {
+                let here = &self.modules[current];
+                match segment.as_str() {
+                    "self" | "*" => {}
+                    "super" => match here.parent {
+                        Some(parent) => current = parent,
+                        None => {
+                            findings.push(Finding::new(
+                                &module.file,
+                                Some(reference.line),
+                                format!("`{}`: `{}` has no parent", path, here.name),
+                            ));
+                            break;
+                        }
+                    },
+                    name => {
+                        if let Some(child) = here.children.get(name) {
+                            current = *child;
+                            continue;
+                        }
+                        // Past a type or an import the path is out of reach
+                        if !here.items.contains(name) && !here.open {
+                            findings.push(Finding::new(
+                                &module.file,
+                                Some(reference.line),
+                                format!("`{}`: no `{}` in `{}`", path, name, here.name),
+                            ));
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+        self.findings.extend(findings);
+    }
+}
+
+impl<'ast> Visit<'ast> for Module {
+    fn visit_item_use(&mut self, item: &'ast syn::ItemUse) {
+        let line = item.use_token.span.start().line;
+        let mut uses = Vec::new();
+        flatten(&item.tree, &mut Vec::new(), &mut uses);
+        for segments in uses {
+            match imported_name(&segments) {
+                Some(name) => {
+                    self.items.insert(name);
+                }
+                None => self.open = true,
+            }
+            let segments = segments.into_iter().map(|(segment, _)| segment).collect();
+            self.references.push(Reference {
+                segments,
+                absolute: item.leading_colon.is_some(),
+                import: true,
+                line,
+            });
+        }
+    }
+
+    // Modules and crates declared in a function body start paths too
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        self.items.insert(item.ident.to_string());
+        visit::visit_item_mod(self, item);
+    }
+
+    fn visit_item_extern_crate(&mut self, item: &'ast syn::ItemExternCrate) {
+        let ident = match &item.rename {
+            Some((_, rename)) => rename,
+            None => &item.ident,
+        };
+        self.items.insert(ident.to_string());
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast syn::Attribute) {
+        let tool = attr
+            .path()
+            .segments
+            .first()
+            .is_some_and(|segment| TOOLS.contains(&segment.ident.to_string().as_str()));
+        if !tool {
+            visit::visit_attribute(self, attr);
+        }
+    }
+
+    /// Records paths that can start at a crate or module; one segment is a
+    /// local or an item, and a capitalised first one a type or `Self`.
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        visit::visit_path(self, path);
+        let Some(first) = path.segments.first() else {
+            return;
+        };
+        let name = first.ident.to_string();
+        if path.segments.len() < 2
+            || name.starts_with(|c: char| c.is_uppercase())
+            || PRIMITIVES.contains(&name.as_str())
+        {
+            return;
+        }
+        self.references.push(Reference {
+            segments: path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect(),
+            absolute: path.leading_colon.is_some(),
+            import: false,
+            line: first.ident.span().start().line,
+        });
+    }
+}
+
+/// The crates `[dependencies]` and `[target.*.dependencies]` make
+/// available, by the name code uses for them.
+fn dependencies(manifest: &toml::Table) -> BTreeSet<String> {
+    let targets = manifest
+        .get("target")
+        .and_then(|target| target.as_table())
+        .into_iter()
+        .flat_map(|targets| targets.values())
+        .filter_map(|target| target.get("dependencies"));
+    manifest
+        .get("dependencies")
+        .into_iter()
+        .chain(targets)
+        .filter_map(|dependencies| dependencies.as_table())
+        .flat_map(|dependencies| dependencies.keys())
+        .map(|name| name.replace('-', "_"))
+        .collect()
+}
+
+/// Every path of a `use` tree, each segment paired with the name it is
+/// imported as when it is the last one (`as` renames, `*` for globs).
+fn flatten(
+    tree: &syn::UseTree,
+    prefix: &mut Vec<(String, Option<String>)>,
+    out: &mut Vec<Vec<(String, Option<String>)>>,
+) {
+    match tree {
+        syn::UseTree::Path(path) => {
+            prefix.push((path.ident.to_string(), None));
+            flatten(&path.tree, prefix, out);
+            prefix.pop();
+        }
+        syn::UseTree::Name(name) => {
+            let mut path = prefix.clone();
+            path.push((name.ident.to_string(), None));
+            out.push(path);
+        }
+        syn::UseTree::Rename(rename) => {
+            let mut path = prefix.clone();
+            path.push((rename.ident.to_string(), Some(rename.rename.to_string())));
+            out.push(path);
+        }
+        syn::UseTree::Glob(_) => {
+            let mut path = prefix.clone();
+            path.push(("*".to_string(), None));
+            out.push(path);
+        }
+        syn::UseTree::Group(group) => {
+            for tree in &group.items {
+                flatten(tree, prefix, out);
+            }
+        }
+    }
+}
+
+/// The name a flattened `use` path brings into scope; `None` for a glob.
+fn imported_name(segments: &[(String, Option<String>)]) -> Option<String> {
+    let (last, rename) = segments.last()?;
+    if let Some(rename) = rename {
+        return Some(rename.clone());
+    }
+    match last.as_str() {
+        "*" => None,
+        "self" if segments.len() > 1 => Some(segments[segments.len() - 2].0.clone()),
+        _ => Some(last.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::PlannedFile;
+
+    const CARGO_TOML: &str =
+        "[package]\nname = \"demo\"\n\n[dependencies]\nlog = \"0.4\"\nserde-json = \"1\"\n";
+
+    /// `file: message` for each finding of a crate made of `files`.
+    fn lint(files: &[(&str, &str)]) -> Vec<String> {
+        let plan = Plan {
+            files: [("Cargo.toml", CARGO_TOML)]
+                .iter()
+                .chain(files)
+                .map(|(path, contents)| PlannedFile {
+                    path: PathBuf::from(path),
+                    contents: contents.as_bytes().to_vec(),
+                })
+                .collect(),
+            extras: Vec::new(),
+            conditional: Vec::new(),
+        };
+        check(&plan)
+            .into_iter()
+            .map(|finding| format!("{}: {}", finding.file.display(), finding.message))
+            .collect()
+    }
+
+    #[test]
+    fn reports_every_defect() {
+        let findings = lint(&[
+            (
+                "src/main.rs",
+                "mod models;\nmod missing;\n\nuse crate::nothing::Here;\nuse undeclared::Thing;\n\n\
+                 #[tokio::main]\nasync fn main() {\n    let _ = crate::models::Foo::new();\n}\n",
+            ),
+            (
+                "src/models.rs",
+                "pub struct User;\n\npub enum ApiError {\n    OpenSsl(openssl::error::Error),\n}\n\n\
+                 fn parent() {\n    super::super::main();\n}\n",
+            ),
+            ("src/orphan.rs", "pub fn unused() {}\n"),
+            ("src/models/broken.rs", "fn {\n"),
+        ]);
+        let expected = [
+            "src/main.rs: `mod missing;` has neither src/missing.rs nor src/missing/mod.rs",
+            "src/main.rs: `use crate::nothing::Here`: no `nothing` in `crate`",
+            "src/main.rs: `use undeclared::Thing`: crate `undeclared` is not a dependency in Cargo.toml",
+            "src/main.rs: `tokio::main`: crate `tokio` is not a dependency in Cargo.toml",
+            "src/main.rs: `crate::models::Foo::new`: no `Foo` in `crate::models`",
+            "src/models.rs: `openssl::error::Error`: crate `openssl` is not a dependency in Cargo.toml",
+            "src/models.rs: `super::super::main`: `crate` has no parent",
+            "src/orphan.rs: not declared with `mod` in any module of the crate",
+            "src/models/broken.rs: not declared with `mod` in any module of the crate",
+        ];
+        for message in expected {
+            assert!(
+                findings.iter().any(|f| f == message),
+                "{}\n{:#?}",
+                message,
+                findings
+            );
+        }
+        assert_eq!(findings.len(), expected.len(), "{:#?}", findings);
+    }
+
+    #[test]
+    fn reports_files_that_do_not_parse() {
+        let findings = lint(&[
+            ("src/main.rs", "mod broken;\n\nfn main() {}\n"),
+            ("src/broken.rs", "fn {\n"),
+        ]);
+        assert_eq!(findings.len(), 1, "{:#?}", findings);
+        assert!(
+            findings[0].starts_with("src/broken.rs: does not parse"),
+            "{:#?}",
+            findings
+        );
+    }
+
+    #[test]
+    fn accepts_paths_that_resolve() {
+        let findings = lint(&[
+            (
+                "src/main.rs",
+                "mod models;\n\nuse models::User;\n\n#[rustfmt::skip]\nfn main() {\n\
+                 \x20   use std::io;\n    let _ = io::stdout();\n    let _ = u64::MAX;\n\
+                 \x20   let _ = ::std::mem::drop(User::new());\n    let _ = crate::models::User::new();\n\
+                 \x20   let _ = models::helpers::count();\n    log::info!(\"{}\", serde_json::json!({}));\n}\n",
+            ),
+            (
+                "src/models/mod.rs",
+                "pub mod helpers;\n\npub struct User;\n\nimpl User {\n\
+                 \x20   pub fn new() -> Self {\n        Self::default_user()\n    }\n\n\
+                 \x20   fn default_user() -> Self {\n        let _ = self::helpers::count();\n        User\n    }\n}\n",
+            ),
+            (
+                "src/models/helpers.rs",
+                "pub fn count() -> usize {\n    super::super::main();\n    0\n}\n",
+            ),
+        ]);
+        assert!(findings.is_empty(), "{:#?}", findings);
+    }
+}
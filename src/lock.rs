@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::manifest::{Extra, Manifest, VarValue};
+use crate::plan::Plan;
+use crate::remote::GitSource;
+use crate::template::{Origin, Template};
+use crate::workspace::Workspace;
+
+/// File in the generated project recording how it was generated.
+pub const LOCK_FILE: &str = ".woragis.toml";
+
+/// File in the generated project holding the files as last rendered, the
+/// common ancestor `upgrade` merges against.
+pub const BASE_FILE: &str = ".woragis.base.toml";
+
+/// Contents of [`LOCK_FILE`].
+///
+/// ```toml
+/// [project]
+/// name = "my-api"
+/// author = "Jane"
+/// extras = ["ci"]
+///
+/// [template]
+/// name = "rest"
+/// version = "0.1.0"
+///
+/// [options]
+/// docker = true
+/// features = ["auth", "cache"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lock {
+    pub project: LockedProject,
+    pub template: LockedTemplate,
+    /// The value of every template variable.
+    #[serde(default)]
+    pub options: BTreeMap<String, VarValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedProject {
+    /// The name as typed, which `project_name` renders to.
+    pub name: String,
+    pub author: String,
+    #[serde(default)]
+    pub extras: Vec<Extra>,
+    /// Set when generated with `--workspace`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<Workspace>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedTemplate {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Where the template came from unless built in or in the user
+    /// registry: `git+<url>#<ref>` or a `--template-path` directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The commit a git `source` resolved to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+impl Lock {
+    pub fn new(
+        template: &Template,
+        project_name: &str,
+        author: &str,
+        options: &BTreeMap<String, VarValue>,
+        extras: &[Extra],
+        workspace: Option<Workspace>,
+    ) -> Lock {
+        let (source, commit) = match &template.origin {
+            Origin::Git { source, commit, .. } => (Some(source.to_string()), Some(commit.clone())),
+            Origin::Local(dir) => (
+                Some(
+                    std::path::absolute(dir)
+                        .unwrap_or_else(|_| dir.clone())
+                        .display()
+                        .to_string(),
+                ),
+                None,
+            ),
+            Origin::Builtin | Origin::User(_) => (None, None),
+        };
+        Lock {
+            project: LockedProject {
+                name: project_name.to_string(),
+                author: author.to_string(),
+                extras: extras.to_vec(),
+                workspace,
+            },
+            template: LockedTemplate {
+                name: template.name.clone(),
+                version: template.manifest.template.version.clone(),
+                source,
+                commit,
+            },
+            options: options.clone(),
+        }
+    }
+
+    /// Resolve the variables of `manifest` from the locked options, then
+    /// `overrides`. Options the template no longer declares are dropped and
+    /// new variables get their defaults.
+    pub fn variables(
+        &self,
+        manifest: &Manifest,
+        overrides: &[(String, String)],
+    ) -> Result<BTreeMap<String, VarValue>, Error> {
+        let locked = self
+            .options
+            .iter()
+            .filter(|(key, _)| manifest.variables.iter().any(|v| &v.name == *key))
+            .map(|(key, value)| (key.clone(), value.to_string()));
+        let overrides: Vec<(String, String)> = locked.chain(overrides.iter().cloned()).collect();
+        manifest.resolve_variables(&overrides)
+    }
+
+    /// Read the lock of the project in `dir`, if it has one.
+    pub fn read(dir: &Path) -> Result<Option<Lock>, Error> {
+        read_toml(&dir.join(LOCK_FILE))
+    }
+
+    pub fn write(&self, dir: &Path) -> Result<(), Error> {
+        let path = dir.join(LOCK_FILE);
+        fs::write(&path, self.to_toml()).map_err(|e| Error::io(&path, e))
+    }
+
+    /// Load the template the project was generated from, pinned to the same
+    /// commit for git templates.
+    pub fn template(&self) -> Result<Template, Error> {
+        self.load(self.template.commit.as_deref())
+    }
+
+    /// Load the current version of the template, for git templates at
+    /// `reference` or else the ref it was first requested at.
+    pub fn latest_template(&self, reference: Option<&str>) -> Result<Template, Error> {
+        self.load(reference)
+    }
+
+    fn load(&self, reference: Option<&str>) -> Result<Template, Error> {
+        let locked = &self.template;
+        match locked.source.as_deref() {
+            Some(source) => match GitSource::parse(source) {
+                Some(git) => Template::from_git(GitSource {
+                    reference: reference.map(str::to_string).or(git.reference.clone()),
+                    ..git
+                }),
+                None => Template::from_path(source),
+            },
+            None => Template::find(&locked.name),
+        }
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("the lock serializes to TOML")
+    }
+}
+
+impl fmt::Display for LockedTemplate {
+    /// `'rest' 0.1.0`, with the abbreviated commit for git templates.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}'", self.name)?;
+        if let Some(version) = &self.version {
+            write!(f, " {}", version)?;
+        }
+        if let Some(commit) = &self.commit {
+            write!(f, " ({})", &commit[..commit.len().min(7)])?;
+        }
+        Ok(())
+    }
+}
+
+/// Contents of [`BASE_FILE`]: every generated text file by path.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl Snapshot {
+    /// The text files of `plan`, minus the lock and snapshot themselves.
+    pub fn of(plan: &Plan) -> Snapshot {
+        let mut snapshot = Snapshot::default();
+        for file in &plan.files {
+            snapshot.insert(&file.path, &file.contents);
+        }
+        snapshot
+    }
+
+    /// Record `contents` for `path`, unless it is binary or a lock file.
+    pub fn insert(&mut self, path: &Path, contents: &[u8]) {
+        if path == Path::new(LOCK_FILE) || path == Path::new(BASE_FILE) {
+            return;
+        }
+        if let Ok(text) = std::str::from_utf8(contents) {
+            self.files.insert(key(path), text.to_string());
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&str> {
+        self.files.get(&key(path)).map(String::as_str)
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.files.keys().map(PathBuf::from)
+    }
+
+    pub fn read(dir: &Path) -> Result<Option<Snapshot>, Error> {
+        read_toml(&dir.join(BASE_FILE))
+    }
+
+    pub fn write(&self, dir: &Path) -> Result<(), Error> {
+        let path = dir.join(BASE_FILE);
+        fs::write(&path, self.to_toml()).map_err(|e| Error::io(&path, e))
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("the snapshot serializes to TOML")
+    }
+}
+
+/// Snapshot keys use `/` on every platform.
+fn key(path: &Path) -> String {
+    path.iter()
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn read_toml<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>, Error> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let source = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+    toml::from_str(&source)
+        .map(Some)
+        .map_err(|e| Error::Project(format!("invalid {}: {}", path.display(), e)))
+}
@@ -1,18 +1,123 @@
-use clap::Parser;
-use std::fs;
-use std::path::Path;
+mod case;
+mod catalog;
+mod component;
+mod config;
+mod embedded;
+mod error;
+mod generate;
+mod generator;
+mod git;
+mod license;
+mod lint;
+mod lock;
+mod manifest;
+mod name;
+mod plan;
+mod project;
+mod prompt;
+mod registry;
+mod remote;
+mod render;
+mod service;
+mod spec;
+mod summary;
+mod template;
+mod upgrade;
+mod wizard;
+mod workspace;
+
+use clap::{CommandFactory, Parser, Subcommand, error::ErrorKind};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use component::Component;
+use config::Config;
+use error::Error;
+use generate::{Conflict, Outcome};
+use generator::Generator;
+use license::License;
+use lock::{Lock, Snapshot};
+use manifest::Extra;
+use plan::Plan;
+use project::Project;
+use prompt::LinePrompter;
+use render::{Context, Renderer};
+use spec::Spec;
+use summary::{GeneratedResource, Hint, Summary};
+use template::Template;
+use workspace::Workspace;
 
 #[derive(Parser)]
 #[command(name = "create_woragis_api")]
 #[command(version = "1.0")]
 #[command(about = "CLI to scaffold a Rust backend", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-    /// The project name
-    name: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// The project name, or a path ending in it (asked for interactively when omitted on a terminal)
+    name: Option<String>,
+
+    /// Directory to create the project in
+    #[arg(long, value_name = "DIR")]
+    path: Option<PathBuf>,
+
+    /// Template to use: a name from `list` (`-` and `_` are interchangeable)
+    /// or a git repository as `git+<url>#<tag|branch|commit>` [default: rest]
+    #[arg(short, long)]
+    template: Option<String>,
+
+    /// Use the template in a local directory (one with a template.toml) instead
+    #[arg(long, value_name = "DIR", conflicts_with = "template")]
+    template_path: Option<String>,
+
+    /// Author name written into the generated project [default: git's
+    /// user.name and user.email]
+    #[arg(long)]
+    author: Option<String>,
+
+    /// License to publish under: writes LICENSE (LICENSE-MIT and
+    /// LICENSE-APACHE for dual) and sets Cargo.toml's `license`
+    #[arg(long, value_enum)]
+    license: Option<License>,
 
-    /// Optional template type (rest, grpc, ai-rest, ai-grpc)
-    #[arg(short, long, default_value = "rest")]
-    template: String,
+    /// Repository URL for Cargo.toml [default: the origin of the git
+    /// repository generated into, or github.com/<github.user>/<name>]
+    #[arg(long, value_name = "URL")]
+    repository: Option<String>,
+
+    /// Apply a `[presets.<NAME>]` from ~/.config/create_woragis/config.toml
+    /// or a .create_woragis.toml above the current directory; it overrides
+    /// their `[defaults]`, and flags override both
+    #[arg(long, value_name = "NAME")]
+    preset: Option<String>,
+
+    /// Create the project described by a YAML, JSON or TOML spec (see
+    /// `schema`): its options, then its resources. Flags override it, and it
+    /// overrides the config and --preset
+    #[arg(long, value_name = "FILE")]
+    from: Option<PathBuf>,
+
+    /// Set a template variable declared in its template.toml (repeatable)
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_key_val)]
+    vars: Vec<(String, String)>,
+
+    /// Features to include, for templates with a `features` variable
+    /// (shorthand for `--var features=...`; see `info <template>`)
+    #[arg(long, value_name = "LIST", value_delimiter = ',')]
+    features: Option<Vec<String>>,
+
+    /// Database backend, e.g. postgres, sqlite, mysql or none for an in-memory
+    /// store (shorthand for `--var database=...`)
+    #[arg(long, value_name = "DB")]
+    db: Option<String>,
+
+    /// Web framework for HTTP templates, e.g. actix or axum (shorthand for
+    /// `--var framework=...`)
+    #[arg(long, value_name = "NAME")]
+    framework: Option<String>,
 
     /// Include Github Actions CI configuration
     #[arg(long)]
@@ -21,67 +126,709 @@ struct Cli {
     /// Include Terraform Infrastructure setup
     #[arg(long)]
     with_infra: bool,
+
+    /// Generate a Cargo workspace: the template as crates/api, and the models
+    /// and utilities every service uses in crates/shared
+    #[arg(long)]
+    workspace: bool,
+
+    /// With --workspace, also generate crates/worker for background jobs
+    #[arg(long, requires = "workspace")]
+    with_worker: bool,
+
+    /// Generate into an existing directory, overwriting files that differ
+    #[arg(long, conflicts_with = "merge")]
+    force: bool,
+
+    /// Generate into an existing directory, resolving files that differ with --on-conflict
+    #[arg(long)]
+    merge: bool,
+
+    /// How --merge resolves existing files [default: prompt on a terminal, skip otherwise]
+    #[arg(long, value_enum, requires = "merge")]
+    on_conflict: Option<Conflict>,
+
+    /// Initialize a git repository and commit the generated files
+    #[arg(long)]
+    git: bool,
+
+    /// Print the files that would be created instead of writing them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// With --dry-run, also print the rendered contents of every file
+    #[arg(long, requires = "dry_run")]
+    show_content: bool,
+
+    /// Print a JSON report (template, options, files with their SHA-256,
+    /// hints) instead of the usual output, and errors as JSON with a stable
+    /// `code`
+    #[arg(long, conflicts_with = "show_content")]
+    json: bool,
+
+    /// Ask for every option step by step, reading answers from stdin
+    #[arg(short, long)]
+    interactive: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List every available template
+    List,
+    /// Show a template's files, options and dependencies
+    Info {
+        /// Template name or alias
+        template: String,
+    },
+    /// Add a component (a feature such as `cache`, or `ci`/`infra`) to an existing project
+    Add {
+        /// Component to add (see `info <template>`)
+        component: String,
+
+        /// Project directory
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        path: PathBuf,
+
+        /// Template the project was generated from, when it has no .woragis.toml
+        #[arg(short, long)]
+        template: Option<String>,
+
+        /// Set a template variable used to render the component's files (repeatable)
+        #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_key_val)]
+        vars: Vec<(String, String)>,
+    },
+    /// Generate code inside an existing project, e.g. `generate resource todo title:string done:bool`
+    Generate {
+        /// Generator to run (see `info <template>`)
+        generator: String,
+
+        /// Name of the thing to generate, e.g. `todo`
+        name: String,
+
+        /// Fields as NAME:TYPE, with a trailing `?` for optional ones (e.g. `due:datetime?`)
+        fields: Vec<String>,
+
+        /// Project directory
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        path: PathBuf,
+
+        /// Template the project was generated from, when it has no .woragis.toml
+        #[arg(short, long)]
+        template: Option<String>,
+    },
+    /// Add a crate generated from a template to the Cargo workspace around
+    /// the current directory, e.g. `new-service billing --template grpc`
+    NewService {
+        /// Name of the new crate
+        name: String,
+
+        /// Directory inside the workspace
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        path: PathBuf,
+
+        /// Template to generate the service from
+        #[arg(short, long, default_value = DEFAULT_TEMPLATE)]
+        template: String,
+
+        /// Use the template in a local directory instead
+        #[arg(long, value_name = "DIR")]
+        template_path: Option<String>,
+
+        /// Author name written into the new crate
+        #[arg(long)]
+        author: Option<String>,
+
+        /// Set a template variable declared in its template.toml (repeatable)
+        #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_key_val)]
+        vars: Vec<(String, String)>,
+    },
+    /// Print the JSON Schema that `--from` specs are validated against
+    Schema,
+    /// Render templates with every combination of their options and check the
+    /// Rust they produce: that it parses, that `mod`s match files, that
+    /// `crate::…` paths resolve and that the crates it names are in Cargo.toml
+    LintTemplates {
+        /// Templates to check [default: all]
+        templates: Vec<String>,
+
+        /// Also check the template in this local directory (repeatable)
+        #[arg(long, value_name = "DIR")]
+        template_path: Vec<String>,
+    },
+    /// Merge the changes of a newer template version into a generated project
+    Upgrade {
+        /// Project directory
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        path: PathBuf,
+
+        /// For git templates, the tag, branch or commit to upgrade to
+        /// (defaults to the ref the project was generated from)
+        #[arg(long = "ref", value_name = "REF")]
+        reference: Option<String>,
+
+        /// Show what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
-fn main() {
-    let mut args = Cli::parse();
+fn main() -> ExitCode {
+    // Known before parsing, so usage errors can be reported as JSON too
+    let json = std::env::args_os().any(|arg| arg == "--json");
+    let result = match Cli::try_parse() {
+        Ok(args) => run(args),
+        Err(e) if json && e.use_stderr() => Err(usage_error(&e)),
+        Err(e) => e.exit(),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) if json => {
+            println!("{}", e.to_json());
+            ExitCode::from(e.exit_code())
+        }
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+fn run(mut args: Cli) -> Result<(), Error> {
+    match &args.command {
+        Some(Command::List) => {
+            catalog::list(&Template::all()?);
+            return Ok(());
+        }
+        Some(Command::Info { template }) => return catalog::info(&Template::find(template)?),
+        Some(Command::Schema) => {
+            print!("{}", spec::SCHEMA);
+            return Ok(());
+        }
+        Some(Command::LintTemplates {
+            templates,
+            template_path,
+        }) => {
+            let templates = templates
+                .iter()
+                .map(|raw| Template::find(raw))
+                .collect::<Result<Vec<_>, _>>()?;
+            let paths = template_path
+                .iter()
+                .map(|raw| Template::from_path(raw))
+                .collect::<Result<Vec<_>, _>>()?;
+            return lint_templates(&templates, &paths);
+        }
+        Some(Command::Add {
+            component,
+            path,
+            template,
+            vars,
+        }) => {
+            let template = template.as_deref().map(Template::find).transpose()?;
+            return add(component, path, template.as_ref(), vars);
+        }
+        Some(Command::Generate {
+            generator,
+            name,
+            fields,
+            path,
+            template,
+        }) => {
+            let template = template.as_deref().map(Template::find).transpose()?;
+            return generate(generator, name, fields, path, template.as_ref());
+        }
+        Some(Command::NewService {
+            name,
+            path,
+            template,
+            template_path,
+            author,
+            vars,
+        }) => {
+            let template = match template_path {
+                Some(dir) => Template::from_path(dir)?,
+                None => Template::find(template)?,
+            };
+            return new_service(name, path, &template, author.as_deref(), vars);
+        }
+        Some(Command::Upgrade {
+            path,
+            reference,
+            dry_run,
+        }) => return upgrade(path, reference.as_deref(), *dry_run),
+        None => {}
+    }
+
+    // Flags win over the spec, then the preset, then the config's defaults
+    let mut settings = Config::load()?.settings(args.preset.as_deref())?;
+    let spec = args.from.as_deref().map(Spec::read).transpose()?;
+    if let Some(spec) = &spec {
+        settings = spec.settings().over(settings);
+        if args.name.is_none() {
+            args.name = Some(spec.name.clone());
+        }
+    }
+    // Resolved here rather than by clap, so an unknown template is reported
+    // as `unknown_template` under --json instead of as a usage error
+    let mut template = match (&args.template_path, &args.template) {
+        (Some(dir), _) => Template::from_path(dir)?,
+        (None, Some(raw)) => Template::find(raw)?,
+        (None, None) => Template::find(settings.template.as_deref().unwrap_or(DEFAULT_TEMPLATE))?,
+    };
+    if let (Some(spec), Some(path)) = (&spec, &args.from) {
+        spec.check(&template, path)?;
+    }
+    args.vars
+        .splice(0..0, settings.variables(&template.manifest));
+    if args.author.is_none() {
+        args.author = settings.author.clone();
+    }
+    if args.license.is_none() {
+        args.license = settings
+            .license
+            .as_deref()
+            .map(License::parse)
+            .transpose()
+            .map_err(Error::Config)?;
+    }
+    args.with_ci |= settings.ci.unwrap_or(false);
+    args.with_infra |= settings.infra.unwrap_or(false);
+    if let Some(features) = args.features.take() {
+        args.vars.push(("features".to_string(), features.join(",")));
+    }
+    if let Some(db) = args.db.take() {
+        args.vars.push(("database".to_string(), db));
+    }
+    if let Some(framework) = args.framework.take() {
+        args.vars.push(("framework".to_string(), framework));
+    }
+
+    // Without any arguments on a terminal, fall back to the wizard
+    let no_args = std::env::args_os().len() == 1;
+    if args.interactive || (no_args && std::io::stdin().is_terminal()) {
+        template = wizard::run(&mut LinePrompter::stdio(), &mut args, template)?;
+    }
+
+    let Some(raw_name) = args.name.clone() else {
+        let error = Cli::command().error(
+            ErrorKind::MissingRequiredArgument,
+            "a project name is required",
+        );
+        if args.json {
+            return Err(usage_error(&error));
+        }
+        error.exit();
+    };
+    let target = name::resolve(&raw_name, args.path.as_deref())?;
+    let name = &target.name;
 
     // If --with-infra is passed, automatically enable --with-ci
     if args.with_infra {
         args.with_ci = true;
     }
 
-    let project_dir = Path::new(&args.name);
+    let template = &template;
+    let manifest = &template.manifest;
+
+    let extras: Vec<Extra> = [(Extra::Ci, args.with_ci), (Extra::Infra, args.with_infra)]
+        .into_iter()
+        .filter_map(|(extra, enabled)| enabled.then_some(extra))
+        .collect();
+    if let Some(extra) = extras.iter().find(|extra| !manifest.supports(**extra)) {
+        return Err(Error::UnsupportedExtra {
+            template: template.name.clone(),
+            extra: extra.to_string(),
+        });
+    }
 
-    if project_dir.exists() {
-        eprintln!("Directory '{}' already exists!", args.name);
-        std::process::exit(1);
+    let workspace = args.workspace.then_some(Workspace {
+        worker: args.with_worker,
+    });
+    if workspace.is_some() && !Workspace::supports(template) {
+        return Err(Error::UnsupportedExtra {
+            template: template.name.clone(),
+            extra: "workspace".to_string(),
+        });
     }
 
-    // Create project directory
-    fs::create_dir(&project_dir).expect("Failed to create project directory");
+    let variables = manifest.resolve_variables(&args.vars)?;
 
-    // Copy template
-    let template_path = format!("templates/{}", args.template);
-    copy_dir_all(&template_path, &project_dir).expect("Failed to copy template");
+    let author = args.author.clone().unwrap_or_else(default_author);
+    let mut context = Context::new(name, &author);
+    context.set_options(&variables, &extras);
+    context.set_workspace(workspace.as_ref());
+    let renderer = Renderer::new(&context);
 
-    // Optional: copy .github/ if --with-ci (including if with_infra is enabled)
-    if args.with_ci {
-        let ci_template = "extras/.github";
-        copy_dir_all(ci_template, &project_dir.join(".github")).expect("Failed to copy CI configs");
+    let mut plan = Plan::build(template, &extras, workspace.as_ref(), &renderer)?;
+    let lock = Lock::new(template, name, &author, &variables, &extras, workspace);
+    plan.add(lock::LOCK_FILE, lock.to_toml());
+    // Taken before --git touches .gitignore and the license and repository
+    // are filled in, so `upgrade` sees those as the user's
+    let snapshot = Snapshot::of(&plan);
+    if let Some(license) = args.license {
+        license.apply(&mut plan, &author);
+    }
+    let outer = target
+        .dir
+        .ancestors()
+        .find(|dir| dir.is_dir())
+        .unwrap_or(Path::new("."));
+    let repository = args
+        .repository
+        .clone()
+        .or_else(|| git::repository(outer, name));
+    if let Some(repository) = repository {
+        plan.set_package("repository", |_| repository.as_str());
+    }
+    if args.git {
+        plan.merge_gitignore(
+            git::BASE_IGNORES
+                .iter()
+                .copied()
+                .chain(manifest.template.gitignore.iter().map(String::as_str))
+                .chain(
+                    extras
+                        .iter()
+                        .flat_map(|extra| extra.gitignore().iter().copied()),
+                ),
+        );
     }
 
-    // Optional: copy terraform/ if --with-infra
-    if args.with_infra {
-        let infra_template = "extras/terraform";
-        copy_dir_all(infra_template, &project_dir.join("terraform")).expect("Failed to copy infra setup");
+    let env = spec.as_ref().map(Spec::env).unwrap_or_default();
+    if !env.is_empty() {
+        plan.set_env(&env);
     }
 
-    println!("✅ Project '{}' created using '{}' template.", args.name, args.template);
-    if args.with_ci {
-        println!("✅ Included GitHub CI (.github/)");
+    plan.add(lock::BASE_FILE, snapshot.to_toml());
+
+    let resources = spec.as_ref().map_or(&[][..], |spec| &spec.resources[..]);
+    let mut summary = Summary::new(&lock, template, &target.dir, args.dry_run);
+    summary.add_env_hints(&plan);
+    if args.dry_run {
+        if args.json {
+            summary.add_planned(&plan);
+            summary.print();
+            return Ok(());
+        }
+        let mut out = io::stdout().lock();
+        let printed = plan
+            .print(
+                &mut out,
+                &target.dir.display().to_string(),
+                args.show_content,
+            )
+            .and_then(|()| {
+                for resource in resources {
+                    writeln!(
+                        out,
+                        "Would generate {} '{}' {}",
+                        resource.generator(),
+                        resource.name,
+                        resource.fields.join(" ")
+                    )?;
+                }
+                Ok(())
+            });
+        return match printed {
+            // Piped into `head` or the like, which has seen enough
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+                Err(Error::io(Path::new("<stdout>"), e))
+            }
+            _ => Ok(()),
+        };
     }
-    if args.with_infra {
-        println!("✅ Included Terraform (terraform/)");
+
+    let conflict = if args.force {
+        Some(Conflict::Overwrite)
+    } else if args.merge {
+        Some(
+            args.on_conflict
+                .unwrap_or(if std::io::stdin().is_terminal() {
+                    Conflict::Prompt
+                } else {
+                    Conflict::Skip
+                }),
+        )
+    } else {
+        None
+    };
+
+    // Write the template, plus .github/ and terraform/ when requested
+    // (--with-infra implies --with-ci), then the spec's resources before the
+    // project is moved into place, so a failing one leaves nothing behind
+    let mut generated = Vec::new();
+    let report = generate::generate(
+        &plan,
+        &target.dir,
+        conflict,
+        &mut LinePrompter::stdio(),
+        &mut |dir| {
+            if resources.is_empty() {
+                return Ok(());
+            }
+            let project = Project::open(dir)?;
+            for resource in resources {
+                let generator = Generator::find(template, resource.generator())?;
+                let fields = generator.parse_fields(&resource.fields)?;
+                let output =
+                    generator::generate(&project, template, &generator, &resource.name, &fields)?;
+                generated.push((generator.name, resource, output));
+            }
+            Ok(())
+        },
+    )?;
+    summary.add_report(&target.dir, &report)?;
+
+    if !args.json {
+        println!(
+            "✅ Project '{}' created in '{}' using '{}' template ({}).",
+            name,
+            target.dir.display(),
+            template.name,
+            manifest.template.description
+        );
+        report.print();
+        if args.with_ci {
+            println!("✅ Included GitHub CI (.github/)");
+        }
+        if args.with_infra {
+            println!("✅ Included Terraform (terraform/)");
+        }
+        if let Some(workspace) = &workspace {
+            let members: Vec<String> = workspace
+                .members()
+                .iter()
+                .map(|member| member.display().to_string())
+                .collect();
+            println!("✅ Laid out as a workspace ({})", members.join(", "));
+        }
+    }
+    for (generator, resource, output) in generated {
+        summary.add_report(&target.dir, &output.report)?;
+        for (file, line) in output.manual {
+            summary.hints.push(Hint::ManualInsert {
+                file,
+                line: line.trim().to_string(),
+            });
+        }
+        if !args.json {
+            println!("✅ Generated {} '{}'", generator, resource.name);
+        }
+        summary.resources.push(GeneratedResource {
+            generator,
+            name: resource.name.clone(),
+        });
+    }
+
+    if args.git {
+        let message = git::scaffold_message(template, &variables, &extras, workspace.as_ref());
+        git::init_and_commit(&target.dir, &message, &author)?;
+        if !args.json {
+            println!("✅ Committed the initial scaffold to git");
+        }
     }
+    if args.json {
+        summary.print();
+    }
+    Ok(())
 }
 
-/// Recursively copy a directory
-fn copy_dir_all(src: &str, dst: &Path) -> std::io::Result<()> {
-    fs::create_dir_all(dst)?; // ✅ Ensure the destination directory exists
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let path = entry.path();
-        let dest_path = dst.join(entry.file_name());
-
-        if path.is_dir() {
-            copy_dir_all(path.to_str().unwrap(), &dest_path)?;
-        } else {
-            if let Some(parent) = dest_path.parent() {
-                fs::create_dir_all(parent)?; // ✅ Create parent directories for files
+/// `add`: bolt a component onto the project in `path`.
+fn add(
+    component: &str,
+    path: &Path,
+    template: Option<&Template>,
+    vars: &[(String, String)],
+) -> Result<(), Error> {
+    let project = Project::open(path)?;
+    let template = project.template(template)?;
+    let component = Component::find(&template, component)?;
+    let report = component::add(&project, &template, &component, vars)?;
+
+    if report
+        .entries
+        .iter()
+        .all(|(_, outcome)| *outcome == Outcome::Unchanged)
+    {
+        println!("✅ '{}' is already part of the project", component.name());
+    } else {
+        println!(
+            "✅ Added '{}' from the '{}' template",
+            component.name(),
+            template.name
+        );
+        report.print();
+        print_conflicts(&report);
+    }
+    Ok(())
+}
+
+/// `generate`: run one of the template's generators inside the project in
+/// `path`.
+fn generate(
+    generator: &str,
+    name: &str,
+    fields: &[String],
+    path: &Path,
+    template: Option<&Template>,
+) -> Result<(), Error> {
+    let project = Project::open(path)?;
+    let template = project.template(template)?;
+    let generator = Generator::find(&template, generator)?;
+    let fields = generator.parse_fields(fields)?;
+    let generated = generator::generate(&project, &template, &generator, name, &fields)?;
+
+    println!("✅ Generated {} '{}'", generator.name, name);
+    generated.report.print();
+    for (file, line) in &generated.manual {
+        println!("⚠️  Add to {} by hand: {}", file.display(), line.trim());
+    }
+    Ok(())
+}
+
+/// `new-service`: add a crate generated from `template` to the workspace
+/// around `path`.
+fn new_service(
+    name: &str,
+    path: &Path,
+    template: &Template,
+    author: Option<&str>,
+    vars: &[(String, String)],
+) -> Result<(), Error> {
+    let author = author.map(str::to_string).unwrap_or_else(default_author);
+    let service = service::new_service(path, name, template, vars, &author)?;
+
+    println!(
+        "✅ Service '{}' created in '{}' using '{}' template ({}).",
+        name,
+        service.root.join(&service.dir).display(),
+        template.name,
+        template.manifest.template.description
+    );
+    service.report.print();
+    print_conflicts(&service.report);
+    if service.listed {
+        println!(
+            "✅ Added '{}' to the workspace members",
+            service.dir.display()
+        );
+    }
+    for unified in &service.unified {
+        println!(
+            "⚠️  '{}' wants {} {}, using the workspace's {}",
+            name, unified.name, unified.wanted, unified.kept
+        );
+    }
+    Ok(())
+}
+
+/// `lint-templates`: lint `templates` and `paths`, or every template when
+/// both are empty.
+fn lint_templates(templates: &[Template], paths: &[Template]) -> Result<(), Error> {
+    let all;
+    let templates: Vec<&Template> = if templates.is_empty() && paths.is_empty() {
+        all = Template::all()?;
+        all.iter().collect()
+    } else {
+        templates.iter().chain(paths).collect()
+    };
+
+    let mut problems = 0;
+    for template in templates {
+        let linted = lint::lint(template)?;
+        let total = linted.combinations.len();
+        if linted.problems.is_empty() {
+            println!(
+                "✅ {}: {} combination(s), no problems",
+                template.name, total
+            );
+            continue;
+        }
+        println!(
+            "❌ {}: {} problem(s) in {} combination(s)",
+            template.name,
+            linted.problems.len(),
+            total
+        );
+        for problem in &linted.problems {
+            let line = problem.line.map(|l| format!(":{}", l)).unwrap_or_default();
+            println!("  {}{}: {}", problem.file.display(), line, problem.message);
+            if problem.combinations.len() == total {
+                println!("      in every combination");
+            } else {
+                println!(
+                    "      in {} of {}, e.g. {}",
+                    problem.combinations.len(),
+                    total,
+                    problem.combinations[0]
+                );
             }
-            fs::copy(&path, &dest_path)?;
         }
+        problems += linted.problems.len();
     }
+    if problems > 0 {
+        return Err(Error::Lint(problems));
+    }
+    Ok(())
+}
+
+/// `upgrade`: bring the project in `path` up to date with its template.
+fn upgrade(path: &Path, reference: Option<&str>, dry_run: bool) -> Result<(), Error> {
+    let project = Project::open(path)?;
+    let upgraded = upgrade::upgrade(&project, reference, dry_run)?;
+    let report = &upgraded.report;
+
+    if report.entries.is_empty() {
+        println!("✅ Already up to date with {}", upgraded.to);
+        return Ok(());
+    }
+    let verb = if dry_run { "Would upgrade" } else { "Upgraded" };
+    println!("✅ {} from {} to {}", verb, upgraded.from, upgraded.to);
+    report.print();
+    print_conflicts(report);
     Ok(())
 }
+
+/// Point out the files of `report` merged with conflict markers.
+fn print_conflicts(report: &generate::Report) {
+    let conflicts = report
+        .entries
+        .iter()
+        .filter(|(_, outcome)| *outcome == Outcome::Conflict)
+        .count();
+    if conflicts > 0 {
+        println!(
+            "⚠️  {} file(s) have conflicts; resolve the <<<<<<< markers by hand",
+            conflicts
+        );
+    }
+}
+
+/// Template used when neither a flag nor the config picks one.
+const DEFAULT_TEMPLATE: &str = "rest";
+
+/// A clap error as an [`Error::Usage`], without clap's `error: ` prefix
+/// and usage hints.
+fn usage_error(error: &clap::Error) -> Error {
+    let message = error.to_string();
+    let first = message.lines().next().unwrap_or_default();
+    Error::Usage(first.trim_start_matches("error: ").to_string())
+}
+
+/// Parse a `KEY=VALUE` pair for `--var`.
+fn parse_key_val(raw: &str) -> Result<(String, String), String> {
+    let (key, value) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", raw))?;
+    Ok((key.trim().to_string(), value.to_string()))
+}
+
+/// Fall back to git's `user.name` and `user.email`, then to the current
+/// user's login name, when `--author` is not given.
+fn default_author() -> String {
+    git::author(Path::new("."))
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
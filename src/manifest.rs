@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::generator::GENERATORS_DIR;
+use crate::render::Renderer;
+use crate::workspace::WORKSPACE_DIR;
+
+/// File name of the manifest every template directory must carry.
+pub const MANIFEST_FILE: &str = "template.toml";
+
+/// A template's `template.toml`.
+///
+/// ```toml
+/// [template]
+/// description = "REST API on actix-web"
+/// version = "0.1.0"
+/// aliases = ["http"]
+/// extras = ["ci", "infra"]
+/// gitignore = [".env"]
+///
+/// [[variables]]
+/// name = "features"
+/// type = "list"
+/// choices = ["auth", "admin"]
+/// requires = { admin = ["auth"] }
+/// default = ["auth"]
+///
+/// [[variables]]
+/// name = "docker"
+/// prompt = "Include a Dockerfile?"
+/// type = "bool"
+/// default = true
+///
+/// [[files]]
+/// path = "Dockerfile"
+/// when = "docker"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub template: TemplateInfo,
+    #[serde(default)]
+    pub variables: Vec<Variable>,
+    #[serde(default)]
+    pub files: Vec<FileRule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateInfo {
+    pub description: String,
+    /// Recorded in the lock file of generated projects; bump it when the
+    /// template changes.
+    pub version: Option<String>,
+    /// Other names the template can be selected by.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Extras this template can be generated with.
+    #[serde(default)]
+    pub extras: Vec<Extra>,
+    /// Patterns added to `.gitignore` when the project is committed with
+    /// `--git`.
+    #[serde(default)]
+    pub gitignore: Vec<String>,
+    /// Files and directories `--workspace` moves into the shared crate,
+    /// along with what they use.
+    #[serde(default)]
+    pub shared: Vec<String>,
+}
+
+/// A value the template needs, with its default and validation rules.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Variable {
+    pub name: String,
+    /// Question shown when asking for the value interactively.
+    pub prompt: Option<String>,
+    #[serde(default, rename = "type")]
+    pub kind: VarKind,
+    /// Used when no value is given. Variables without a default are required.
+    pub default: Option<VarValue>,
+    /// Allowed values; for lists, every element must be one of them.
+    #[serde(default)]
+    pub choices: Vec<String>,
+    /// For lists, choices that need others of the same list, which are
+    /// then added automatically (`requires = { admin = ["auth"] }`).
+    #[serde(default)]
+    pub requires: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VarKind {
+    #[default]
+    String,
+    Bool,
+    List,
+}
+
+/// The value of a template variable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VarValue {
+    Bool(bool),
+    List(Vec<String>),
+    String(String),
+}
+
+/// Includes a file (or every file under a directory) only when `when`
+/// evaluates to true against the template variables.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileRule {
+    pub path: String,
+    pub when: String,
+}
+
+/// Optional additions shared by every template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Extra {
+    /// GitHub Actions CI configuration
+    Ci,
+    /// Terraform infrastructure setup
+    Infra,
+}
+
+impl Extra {
+    /// Directory under `extras/` holding the files.
+    pub fn source_dir(self) -> &'static str {
+        match self {
+            Extra::Ci => ".github",
+            Extra::Infra => "terraform",
+        }
+    }
+
+    /// Directory inside the generated project receiving the files.
+    pub fn target_dir(self) -> &'static str {
+        self.source_dir()
+    }
+
+    /// `.gitignore` patterns for files the extra's tooling creates locally.
+    pub fn gitignore(self) -> &'static [&'static str] {
+        match self {
+            Extra::Ci => &[],
+            Extra::Infra => &[
+                "terraform/.terraform/",
+                "terraform/*.tfstate",
+                "terraform/*.tfstate.*",
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Extra {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Extra::Ci => write!(f, "ci"),
+            Extra::Infra => write!(f, "infra"),
+        }
+    }
+}
+
+impl From<VarValue> for minijinja::Value {
+    fn from(value: VarValue) -> Self {
+        match value {
+            VarValue::Bool(b) => b.into(),
+            VarValue::List(items) => items.into(),
+            VarValue::String(s) => s.into(),
+        }
+    }
+}
+
+impl fmt::Display for VarValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VarValue::Bool(b) => write!(f, "{}", b),
+            VarValue::List(items) => write!(f, "{}", items.join(",")),
+            VarValue::String(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl Manifest {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let manifest: Manifest = toml::from_str(source).map_err(|e| e.to_string())?;
+        for variable in &manifest.variables {
+            if let Some(default) = &variable.default {
+                variable.check(default)?;
+            }
+            let required = variable
+                .requires
+                .iter()
+                .flat_map(|(choice, required)| std::iter::once(choice).chain(required));
+            for choice in required {
+                if !variable.choices.contains(choice) {
+                    return Err(format!(
+                        "'{}' requires unknown choice '{}'",
+                        variable.name, choice
+                    ));
+                }
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Resolve every declared variable from `overrides` (`KEY=VALUE` pairs
+    /// from the command line), falling back to defaults.
+    pub fn resolve_variables(
+        &self,
+        overrides: &[(String, String)],
+    ) -> Result<BTreeMap<String, VarValue>, Error> {
+        if let Some((key, _)) = overrides
+            .iter()
+            .find(|(key, _)| !self.variables.iter().any(|v| &v.name == key))
+        {
+            return Err(Error::Variable(format!(
+                "Unknown template variable '{}'",
+                key
+            )));
+        }
+
+        let mut values = BTreeMap::new();
+        for variable in &self.variables {
+            let value = match overrides
+                .iter()
+                .rev()
+                .find(|(key, _)| key == &variable.name)
+            {
+                Some((_, raw)) => variable.parse(raw).map_err(Error::Variable)?,
+                None => variable.default.clone().ok_or_else(|| {
+                    Error::Variable(format!("Missing value for variable '{}'", variable.name))
+                })?,
+            };
+            values.insert(variable.name.clone(), variable.complete(value));
+        }
+        Ok(values)
+    }
+
+    /// Whether `path` (relative to the template root) should be generated.
+    ///
+    /// The manifest and generators are never copied; other files are included unless
+    /// a matching rule's condition is false.
+    pub fn includes(&self, path: &Path, renderer: &Renderer) -> Result<bool, minijinja::Error> {
+        if path == Path::new(MANIFEST_FILE)
+            || path.starts_with(GENERATORS_DIR)
+            || path.starts_with(WORKSPACE_DIR)
+        {
+            return Ok(false);
+        }
+        for rule in self.rules_for(path) {
+            if !renderer.eval(&rule.when)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// The file rules applying to `path`.
+    pub fn rules_for<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a FileRule> {
+        self.files
+            .iter()
+            .filter(move |rule| path.starts_with(&rule.path))
+    }
+
+    pub fn supports(&self, extra: Extra) -> bool {
+        self.template.extras.contains(&extra)
+    }
+}
+
+impl Variable {
+    /// Parse a raw string (from `--var` or a prompt) into a checked value.
+    pub fn parse(&self, raw: &str) -> Result<VarValue, String> {
+        let raw = raw.trim();
+        let value = match self.kind {
+            VarKind::String => VarValue::String(raw.to_string()),
+            VarKind::Bool => match raw.to_lowercase().as_str() {
+                "true" | "yes" | "y" | "1" => VarValue::Bool(true),
+                "false" | "no" | "n" | "0" => VarValue::Bool(false),
+                _ => return Err(format!("'{}' expects yes or no, got '{}'", self.name, raw)),
+            },
+            VarKind::List => VarValue::List(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            ),
+        };
+        self.check(&value)?;
+        Ok(value)
+    }
+
+    /// Add the choices the elements of a list value require.
+    pub fn complete(&self, value: VarValue) -> VarValue {
+        let VarValue::List(mut items) = value else {
+            return value;
+        };
+        let mut i = 0;
+        while i < items.len() {
+            for required in self.requires.get(&items[i]).into_iter().flatten() {
+                if !items.contains(required) {
+                    items.push(required.clone());
+                }
+            }
+            i += 1;
+        }
+        VarValue::List(items)
+    }
+
+    fn check(&self, value: &VarValue) -> Result<(), String> {
+        let items: Vec<&String> = match (self.kind, value) {
+            (VarKind::String, VarValue::String(s)) => vec![s],
+            (VarKind::Bool, VarValue::Bool(_)) => vec![],
+            (VarKind::List, VarValue::List(items)) => items.iter().collect(),
+            _ => {
+                return Err(format!(
+                    "'{}' has the wrong type for a {:?} variable",
+                    self.name, self.kind
+                ));
+            }
+        };
+        if self.choices.is_empty() {
+            return Ok(());
+        }
+        match items.iter().find(|item| !self.choices.contains(item)) {
+            Some(item) => Err(format!(
+                "Invalid value '{}' for '{}' (expected one of: {})",
+                item,
+                self.name,
+                self.choices.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
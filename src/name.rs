@@ -0,0 +1,233 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::case;
+use crate::error::Error;
+
+/// Keywords that cannot be used as a crate name.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "union", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Names Cargo refuses or reserves: standard library crates, artifact
+/// directories and names Windows cannot use for files.
+const RESERVED: &[&str] = &[
+    "alloc",
+    "core",
+    "proc_macro",
+    "std",
+    "test",
+    "build",
+    "deps",
+    "examples",
+    "incremental",
+    "aux",
+    "com1",
+    "com2",
+    "com3",
+    "com4",
+    "com5",
+    "com6",
+    "com7",
+    "com8",
+    "com9",
+    "con",
+    "lpt1",
+    "lpt2",
+    "lpt3",
+    "lpt4",
+    "lpt5",
+    "lpt6",
+    "lpt7",
+    "lpt8",
+    "lpt9",
+    "nul",
+    "prn",
+];
+
+/// The project's name and the directory it is generated into.
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub name: String,
+    pub dir: PathBuf,
+}
+
+/// Split what the user typed into a project name and a directory.
+///
+/// The last path component is the name, so `./foo/bar-api` generates
+/// `bar-api` into `./foo/bar-api`, and `.` uses the current directory's
+/// name. `base` (from `--path`) is prepended to the directory.
+pub fn resolve(raw: &str, base: Option<&Path>) -> Result<Target, Error> {
+    let given = Path::new(raw);
+    let dir = match base {
+        Some(base) => base.join(given),
+        None => given.to_path_buf(),
+    };
+
+    let name = match given.components().next_back() {
+        Some(Component::Normal(name)) => name.to_string_lossy().into_owned(),
+        _ => std::path::absolute(&dir)
+            .ok()
+            .and_then(|dir| normalized_file_name(&dir))
+            .ok_or_else(|| Error::InvalidName(format!("cannot derive a name from '{}'", raw)))?,
+    };
+
+    validate(&name).map_err(Error::InvalidName)?;
+    Ok(Target { name, dir })
+}
+
+/// Check `name` against Cargo's package naming rules.
+pub fn validate(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("the project name cannot be empty".to_string());
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+    {
+        return Err(format!(
+            "invalid character '{}' in '{}' (use letters, digits, '-' and '_')",
+            c, name
+        ));
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(format!("'{}' cannot start with a digit", name));
+    }
+
+    // Templates render the snake_case name, which drops '-' and '_'
+    let crate_name = case::snake_case(name);
+    if !crate_name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(format!(
+            "'{}' must start with a letter once '-' and '_' are dropped",
+            name
+        ));
+    }
+    if is_keyword(&crate_name) {
+        return Err(format!("'{}' is a Rust keyword", name));
+    }
+    if RESERVED.contains(&crate_name.as_str()) {
+        return Err(format!("'{}' is reserved by Cargo", name));
+    }
+    Ok(())
+}
+
+/// Whether `word` is a Rust keyword.
+pub fn is_keyword(word: &str) -> bool {
+    KEYWORDS.contains(&word)
+}
+
+/// Last normal component of `path` once `.` and `..` are applied.
+fn normalized_file_name(path: &Path) -> Option<String> {
+    let mut parts: Vec<&std::ffi::OsStr> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part),
+            Component::ParentDir => {
+                parts.pop();
+            }
+            _ => {}
+        }
+    }
+    parts.last().map(|part| part.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cargo_names_are_accepted() {
+        for name in [
+            "api", "my-api", "my_api", "MyApi", "api2", "stdlib", "tests",
+        ] {
+            assert_eq!(validate(name), Ok(()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn keywords_are_rejected_in_any_spelling() {
+        for name in ["fn", "type", "async", "yield", "Fn", "SELF"] {
+            let error = validate(name).unwrap_err();
+            assert!(error.contains("is a Rust keyword"), "{}: {}", name, error);
+        }
+    }
+
+    #[test]
+    fn reserved_names_are_rejected() {
+        for name in [
+            "std",
+            "core",
+            "proc-macro",
+            "test",
+            "build",
+            "con",
+            "Nul",
+            "lpt1",
+        ] {
+            let error = validate(name).unwrap_err();
+            assert!(
+                error.contains("is reserved by Cargo"),
+                "{}: {}",
+                name,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_names_are_rejected() {
+        assert!(validate("").unwrap_err().contains("cannot be empty"));
+        assert!(
+            validate("1api")
+                .unwrap_err()
+                .contains("cannot start with a digit")
+        );
+        assert!(
+            validate("my.api")
+                .unwrap_err()
+                .contains("invalid character '.'")
+        );
+        assert!(
+            validate("my api")
+                .unwrap_err()
+                .contains("invalid character ' '")
+        );
+        assert!(
+            validate("café")
+                .unwrap_err()
+                .contains("invalid character 'é'")
+        );
+        for name in ["_", "__", "-", "_-_", "_1api", "-2"] {
+            let error = validate(name).unwrap_err();
+            assert!(
+                error.contains("must start with a letter"),
+                "{}: {}",
+                name,
+                error
+            );
+        }
+        assert_eq!(validate("_api"), Ok(()));
+    }
+
+    #[test]
+    fn the_last_component_is_the_name() {
+        let target = resolve("./foo/bar-api", None).unwrap();
+        assert_eq!(target.name, "bar-api");
+        assert_eq!(target.dir, Path::new("./foo/bar-api"));
+
+        let target = resolve("bar-api", Some(Path::new("/srv"))).unwrap();
+        assert_eq!(target.name, "bar-api");
+        assert_eq!(target.dir, Path::new("/srv/bar-api"));
+
+        let target = resolve("..", Some(Path::new("/srv/projects/my-api/src"))).unwrap();
+        assert_eq!(target.name, "my-api");
+
+        assert!(matches!(
+            resolve("foo/fn", None),
+            Err(Error::InvalidName(_))
+        ));
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use toml_edit::{DocumentMut, Item};
+
+use crate::embedded;
+use crate::error::Error;
+use crate::manifest::Extra;
+use crate::render::Renderer;
+use crate::template::{Template, TemplateFile};
+use crate::workspace::Workspace;
+
+/// Everything a generation run would write, rendered in memory.
+pub struct Plan {
+    /// Files relative to the project root, in template order.
+    pub files: Vec<PlannedFile>,
+    pub extras: Vec<Extra>,
+    /// Files guarded by a manifest rule, and whether they made it in.
+    pub conditional: Vec<Conditional>,
+}
+
+pub struct PlannedFile {
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
+}
+
+pub struct Conditional {
+    pub path: PathBuf,
+    pub when: String,
+    pub included: bool,
+}
+
+impl Plan {
+    /// Render `template` plus the selected `extras`, laid out as a
+    /// `workspace` if given.
+    pub fn build(
+        template: &Template,
+        extras: &[Extra],
+        workspace: Option<&Workspace>,
+        renderer: &Renderer,
+    ) -> Result<Plan, Error> {
+        let manifest = &template.manifest;
+        let mut conditional = Vec::new();
+        let root = template.root();
+        let rendered = render_files(&template.files, &root, renderer, |path| {
+            let included = manifest.includes(path, renderer)?;
+            for rule in manifest.rules_for(path) {
+                conditional.push(Conditional {
+                    path: path.to_path_buf(),
+                    when: rule.when.clone(),
+                    included,
+                });
+            }
+            Ok(included)
+        })?;
+
+        let mut files: Vec<PlannedFile> = rendered
+            .into_iter()
+            .map(|(path, contents)| PlannedFile { path, contents })
+            .collect();
+        if let Some(workspace) = workspace {
+            workspace.apply(template, renderer, &mut files)?;
+        }
+
+        files.extend(render_extras(extras, renderer)?);
+
+        Ok(Plan {
+            files,
+            extras: extras.to_vec(),
+            conditional,
+        })
+    }
+
+    /// Add a file that does not come from the template, replacing any
+    /// planned file at the same path.
+    pub fn add(&mut self, path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) {
+        let path = path.into();
+        self.files.retain(|file| file.path != path);
+        self.files.push(PlannedFile {
+            path,
+            contents: contents.into(),
+        });
+    }
+
+    /// Append `patterns` missing from the planned `.gitignore`, creating it
+    /// if the template has none.
+    pub fn merge_gitignore<'a>(&mut self, patterns: impl IntoIterator<Item = &'a str>) {
+        let index = match self
+            .files
+            .iter()
+            .position(|file| file.path == Path::new(".gitignore"))
+        {
+            Some(index) => index,
+            None => {
+                self.files.push(PlannedFile {
+                    path: PathBuf::from(".gitignore"),
+                    contents: Vec::new(),
+                });
+                self.files.len() - 1
+            }
+        };
+
+        let file = &mut self.files[index];
+        let mut text = String::from_utf8_lossy(&file.contents).into_owned();
+        for pattern in patterns {
+            if text.lines().any(|line| line.trim() == pattern) {
+                continue;
+            }
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(pattern);
+            text.push('\n');
+        }
+        file.contents = text.into_bytes();
+    }
+
+    /// Set `KEY=VALUE` lines in the planned `.env.example`, replacing a
+    /// key's existing line or appending it, and creating the file if the
+    /// template has none.
+    pub fn set_env(&mut self, vars: &[(String, String)]) {
+        let path = Path::new(".env.example");
+        let mut text = match self.files.iter().find(|file| file.path == path) {
+            Some(file) => String::from_utf8_lossy(&file.contents).into_owned(),
+            None => String::new(),
+        };
+        for (key, value) in vars {
+            let line = format!("{}={}", key, value);
+            let prefix = format!("{}=", key);
+            match text.lines().position(|l| l.starts_with(&prefix)) {
+                Some(index) => {
+                    let mut lines: Vec<&str> = text.lines().collect();
+                    lines[index] = &line;
+                    text = lines.join("\n") + "\n";
+                }
+                None => {
+                    if !text.is_empty() && !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+                    text.push_str(&line);
+                    text.push('\n');
+                }
+            }
+        }
+        self.add(path, text);
+    }
+
+    /// Append `text` to the planned file at `path` after a blank line, if
+    /// the template has one.
+    pub fn append(&mut self, path: impl AsRef<Path>, text: &str) {
+        let Some(file) = self
+            .files
+            .iter_mut()
+            .find(|file| file.path == path.as_ref())
+        else {
+            return;
+        };
+        let mut contents = String::from_utf8_lossy(&file.contents)
+            .trim_end()
+            .to_string();
+        if !contents.is_empty() {
+            contents.push_str("\n\n");
+        }
+        contents.push_str(text);
+        file.contents = contents.into_bytes();
+    }
+
+    /// Set `key` in the `[package]` of every planned `Cargo.toml` (each
+    /// member of a workspace) to `value` of the manifest's directory,
+    /// keeping the rest of its formatting.
+    pub fn set_package<V: Into<toml_edit::Value>>(
+        &mut self,
+        key: &str,
+        value: impl Fn(&Path) -> V,
+    ) {
+        for file in &mut self.files {
+            if file.path.file_name() != Some("Cargo.toml".as_ref()) {
+                continue;
+            }
+            let dir = file.path.parent().unwrap_or(Path::new(""));
+            let Ok(mut cargo) = String::from_utf8_lossy(&file.contents).parse::<DocumentMut>()
+            else {
+                continue;
+            };
+            let Some(package) = cargo.get_mut("package").and_then(Item::as_table_mut) else {
+                continue;
+            };
+            package.insert(key, toml_edit::value(value(dir)));
+            file.contents = cargo.to_string().into_bytes();
+        }
+    }
+
+    /// Write every planned file below `root`.
+    pub fn write(&self, root: &Path) -> Result<(), Error> {
+        for file in &self.files {
+            let dest_path = root.join(&file.path);
+            if let Some(parent) = dest_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+            }
+            fs::write(&dest_path, &file.contents).map_err(|e| Error::io(&dest_path, e))?;
+        }
+        Ok(())
+    }
+
+    /// Write the planned file tree, the chosen extras and conditional files,
+    /// and optionally the rendered contents of every file to `out`.
+    pub fn print(&self, out: &mut impl Write, root: &str, show_content: bool) -> io::Result<()> {
+        writeln!(out, "{}/", root)?;
+        print_tree(out, &tree(&self.files), "")?;
+
+        writeln!(out)?;
+        if self.extras.is_empty() {
+            writeln!(out, "Extras: none")?;
+        } else {
+            let extras: Vec<String> = self
+                .extras
+                .iter()
+                .map(|extra| format!("{} ({}/)", extra, extra.target_dir()))
+                .collect();
+            writeln!(out, "Extras: {}", extras.join(", "))?;
+        }
+
+        if !self.conditional.is_empty() {
+            writeln!(out, "Conditional files:")?;
+            for file in &self.conditional {
+                let mark = if file.included { '+' } else { '-' };
+                writeln!(
+                    out,
+                    "  {} {}  (when {})",
+                    mark,
+                    file.path.display(),
+                    file.when
+                )?;
+            }
+        }
+
+        if show_content {
+            for file in &self.files {
+                writeln!(out)?;
+                writeln!(out, "==> {} <==", file.path.display())?;
+                match std::str::from_utf8(&file.contents) {
+                    Ok(text) => write!(out, "{}", text)?,
+                    Err(_) => writeln!(out, "(binary, {} bytes)", file.contents.len())?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Render the files of `extras`, each into its target directory.
+pub fn render_extras(extras: &[Extra], renderer: &Renderer) -> Result<Vec<PlannedFile>, Error> {
+    let mut files = Vec::new();
+    for extra in extras {
+        let dir = embedded::extra(extra.source_dir()).expect("extras are embedded");
+        let target = Path::new(extra.target_dir());
+        let sources = embedded::template_files(dir);
+        for (path, contents) in render_files(&sources, target, renderer, |_| Ok(true))? {
+            files.push(PlannedFile {
+                path: target.join(path),
+                contents,
+            });
+        }
+    }
+    Ok(files)
+}
+
+/// Render template files in memory.
+///
+/// `include` is asked about each relative path; accepted files then have both
+/// their path and contents rendered through `renderer`. Errors name the file
+/// below `root`.
+pub fn render_files(
+    files: &[TemplateFile],
+    root: &Path,
+    renderer: &Renderer,
+    mut include: impl FnMut(&Path) -> Result<bool, minijinja::Error>,
+) -> Result<Vec<(PathBuf, Vec<u8>)>, Error> {
+    let render_error = |path: &Path, e: minijinja::Error| Error::Render {
+        path: root.join(path),
+        message: e.to_string(),
+    };
+    let mut rendered = Vec::new();
+    for file in files {
+        if !include(&file.path).map_err(|e| render_error(&file.path, e))? {
+            continue;
+        }
+        let path = renderer
+            .render_path(&file.path)
+            .map_err(|e| render_error(&file.path, e))?;
+        let contents = renderer
+            .render_bytes(&file.contents)
+            .map_err(|e| render_error(&file.path, e))?;
+        rendered.push((path, contents));
+    }
+    Ok(rendered)
+}
+
+/// A directory listing: `None` marks a file, `Some` a subdirectory.
+#[derive(Default)]
+struct Node(BTreeMap<String, Option<Node>>);
+
+fn tree(files: &[PlannedFile]) -> Node {
+    let mut root = Node::default();
+    for file in files {
+        let parts: Vec<String> = file
+            .path
+            .iter()
+            .map(|part| part.to_string_lossy().into_owned())
+            .collect();
+        let Some((name, dirs)) = parts.split_last() else {
+            continue;
+        };
+        let mut node = &mut root;
+        for dir in dirs {
+            node = node
+                .0
+                .entry(dir.clone())
+                .or_insert_with(|| Some(Node::default()))
+                .get_or_insert_with(Node::default);
+        }
+        node.0.entry(name.clone()).or_insert(None);
+    }
+    root
+}
+
+fn print_tree(out: &mut impl Write, node: &Node, prefix: &str) -> io::Result<()> {
+    let count = node.0.len();
+    for (i, (name, child)) in node.0.iter().enumerate() {
+        let last = i + 1 == count;
+        let branch = if last { "└── " } else { "├── " };
+        match child {
+            Some(dir) => {
+                writeln!(out, "{}{}{}/", prefix, branch, name)?;
+                let indent = if last { "    " } else { "│   " };
+                print_tree(out, dir, &format!("{}{}", prefix, indent))?;
+            }
+            None => writeln!(out, "{}{}{}", prefix, branch, name)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(paths: &[&str]) -> Plan {
+        Plan {
+            files: paths
+                .iter()
+                .map(|path| PlannedFile {
+                    path: PathBuf::from(path),
+                    contents: b"text\n".to_vec(),
+                })
+                .collect(),
+            extras: vec![Extra::Ci],
+            conditional: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn print_writes_the_tree() {
+        let mut out = Vec::new();
+        plan(&["src/main.rs", "Cargo.toml", "src/web.rs"])
+            .print(&mut out, "demo", false)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "demo/\n├── Cargo.toml\n└── src/\n    ├── main.rs\n    └── web.rs\n\nExtras: ci (.github/)\n"
+        );
+    }
+
+    /// A pipe whose reader went away, like `head` after its lines.
+    struct Closed;
+
+    impl Write for Closed {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn print_reports_a_closed_pipe() {
+        let error = plan(&["Cargo.toml"])
+            .print(&mut Closed, "demo", true)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn terraform_publishes_no_port_for_workers() {
+        let mut context = crate::render::Context::new("demo", "Test");
+        context.set_options(&Default::default(), &[Extra::Infra]);
+        context.set_workspace(Some(&Workspace { worker: true }));
+        let files = render_extras(&[Extra::Infra], &Renderer::new(&context)).unwrap();
+        let main = files
+            .iter()
+            .find(|file| file.path == Path::new("terraform/main.tf"))
+            .unwrap();
+        let main = String::from_utf8_lossy(&main.contents);
+
+        assert!(
+            main.contains("api = { kind = \"http\", port = 8080 }"),
+            "{}",
+            main
+        );
+        assert!(
+            main.contains("worker = { kind = \"worker\", port = null }"),
+            "{}",
+            main
+        );
+    }
+}
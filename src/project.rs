@@ -0,0 +1,101 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml_edit::DocumentMut;
+
+use crate::error::Error;
+use crate::lock::{self, Lock};
+use crate::template::Template;
+
+/// An already generated project, as modified by `add` and `generate`.
+pub struct Project {
+    pub root: PathBuf,
+    /// The package name from `Cargo.toml`.
+    pub name: String,
+    /// The first package author, or an empty string.
+    pub author: String,
+}
+
+impl Project {
+    /// Open the project in `root`, which must hold a `Cargo.toml`: a
+    /// package, or the workspace generated by `--workspace`.
+    pub fn open(root: &Path) -> Result<Project, Error> {
+        let cargo_path = root.join("Cargo.toml");
+        let cargo = fs::read_to_string(&cargo_path).map_err(|_| {
+            Error::Project(format!(
+                "No Cargo.toml in '{}'; run this inside a generated project",
+                root.display()
+            ))
+        })?;
+        let cargo: DocumentMut = cargo
+            .parse()
+            .map_err(|e| Error::Project(format!("{}: {}", cargo_path.display(), e)))?;
+        if cargo.get("package").is_none() && cargo.get("workspace").is_some() {
+            // A `--workspace` project: its root manifest has no package, the
+            // lock file records what it was generated as
+            let lock = Lock::read(root)?.ok_or_else(|| {
+                Error::Project(format!(
+                    "'{}' is a workspace without a {}; run this inside a generated project",
+                    root.display(),
+                    lock::LOCK_FILE
+                ))
+            })?;
+            return Ok(Project {
+                root: root.to_path_buf(),
+                name: lock.project.name,
+                author: lock.project.author,
+            });
+        }
+        let package = cargo.get("package");
+        let name = package
+            .and_then(|package| package.get("name"))
+            .and_then(|name| name.as_str())
+            .ok_or_else(|| {
+                Error::Project(format!("{} has no package name", cargo_path.display()))
+            })?;
+        let author = package
+            .and_then(|package| package.get("authors"))
+            .and_then(|authors| authors.as_array())
+            .and_then(|authors| authors.get(0))
+            .and_then(|author| author.as_str())
+            .unwrap_or_default();
+
+        Ok(Project {
+            root: root.to_path_buf(),
+            name: name.to_string(),
+            author: author.to_string(),
+        })
+    }
+
+    /// The template the project was generated from: `template` when given,
+    /// otherwise the one recorded in its lock file.
+    pub fn template(&self, template: Option<&Template>) -> Result<Template, Error> {
+        match (template, Lock::read(&self.root)?) {
+            (Some(template), _) => Ok(template.clone()),
+            (None, Some(lock)) => lock.template(),
+            (None, None) => Err(Error::Project(format!(
+                "'{}' has no {}; pass --template to name the template it was generated from",
+                self.root.display(),
+                lock::LOCK_FILE
+            ))),
+        }
+    }
+
+    /// Read a file of the project, `None` if it does not exist.
+    pub fn read(&self, path: &Path) -> Option<Vec<u8>> {
+        fs::read(self.root.join(path)).ok()
+    }
+
+    /// Write `files` (relative paths) into the project, creating parent
+    /// directories as needed.
+    pub fn write(&self, files: Vec<(PathBuf, Vec<u8>)>) -> Result<(), Error> {
+        for (path, contents) in files {
+            let dest_path = self.root.join(path);
+            if let Some(parent) = dest_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+            }
+            fs::write(&dest_path, contents).map_err(|e| Error::io(&dest_path, e))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use std::io::{self, BufRead, Write};
+
+use crate::error::Error;
+
+/// Asks the user questions one line at a time.
+///
+/// Everything the wizard needs is built on [`Prompter::ask`], so a scripted
+/// implementation (or [`LinePrompter`] over a pipe) can answer for the user.
+pub trait Prompter {
+    /// Ask a free-form question. An empty answer yields `default`.
+    fn ask(&mut self, question: &str, default: Option<&str>) -> Result<String, Error>;
+
+    /// Show a message that needs no answer, e.g. why an answer was rejected.
+    fn say(&mut self, message: &str) -> Result<(), Error>;
+
+    /// Ask until a non-empty answer is given.
+    fn input(&mut self, question: &str, default: Option<&str>) -> Result<String, Error> {
+        loop {
+            let answer = self.ask(question, default)?;
+            if !answer.is_empty() {
+                return Ok(answer);
+            }
+            self.say("  A value is required.")?;
+        }
+    }
+
+    /// Yes/no question.
+    fn confirm(&mut self, question: &str, default: bool) -> Result<bool, Error> {
+        let hint = if default { "Y/n" } else { "y/N" };
+        loop {
+            let answer = self.ask(&format!("{} [{}]", question, hint), None)?;
+            match answer.to_lowercase().as_str() {
+                "" => return Ok(default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => self.say("  Please answer yes or no.")?,
+            }
+        }
+    }
+
+    /// Pick one of `choices`, either by number or by name.
+    fn select(
+        &mut self,
+        question: &str,
+        choices: &[String],
+        default: &str,
+    ) -> Result<String, Error> {
+        let question = format!("{} ({})", question, numbered(choices));
+        loop {
+            let answer = self.ask(&question, Some(default))?;
+            match pick(&answer, choices) {
+                Some(choice) => return Ok(choice.clone()),
+                None => self.say(&format!("  '{}' is not one of the choices.", answer))?,
+            }
+        }
+    }
+
+    /// Pick any number of `choices` as a comma-separated list. `none` picks
+    /// nothing.
+    fn multi_select(
+        &mut self,
+        question: &str,
+        choices: &[String],
+        defaults: &[String],
+    ) -> Result<Vec<String>, Error> {
+        let question = format!("{} ({}; comma-separated)", question, numbered(choices));
+        let default = if defaults.is_empty() {
+            "none".to_string()
+        } else {
+            defaults.join(",")
+        };
+        'ask: loop {
+            let answer = self.ask(&question, Some(&default))?;
+            if answer.eq_ignore_ascii_case("none") {
+                return Ok(Vec::new());
+            }
+            let mut picked = Vec::new();
+            for item in answer.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+                match pick(item, choices) {
+                    Some(choice) if !picked.contains(choice) => picked.push(choice.clone()),
+                    Some(_) => {}
+                    None => {
+                        self.say(&format!("  '{}' is not one of the choices.", item))?;
+                        continue 'ask;
+                    }
+                }
+            }
+            return Ok(picked);
+        }
+    }
+}
+
+/// A [`Prompter`] reading answers line by line from any reader, e.g. the
+/// terminal or a piped script of answers.
+pub struct LinePrompter<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> LinePrompter<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        LinePrompter { input, output }
+    }
+}
+
+impl LinePrompter<io::StdinLock<'static>, io::Stderr> {
+    /// Prompt on stderr so stdout stays clean for the final report.
+    pub fn stdio() -> Self {
+        LinePrompter::new(io::stdin().lock(), io::stderr())
+    }
+}
+
+impl<R: BufRead, W: Write> LinePrompter<R, W> {
+    fn read_answer(&mut self, question: &str, default: Option<&str>) -> io::Result<String> {
+        match default {
+            Some(default) => write!(self.output, "? {} [{}]: ", question, default)?,
+            None => write!(self.output, "? {}: ", question)?,
+        }
+        self.output.flush()?;
+
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "no more answers on stdin",
+            ));
+        }
+        Ok(line)
+    }
+}
+
+impl<R: BufRead, W: Write> Prompter for LinePrompter<R, W> {
+    fn ask(&mut self, question: &str, default: Option<&str>) -> Result<String, Error> {
+        let line = self.read_answer(question, default).map_err(Error::Prompt)?;
+        let answer = line.trim();
+        Ok(match (answer.is_empty(), default) {
+            (true, Some(default)) => default.to_string(),
+            _ => answer.to_string(),
+        })
+    }
+
+    fn say(&mut self, message: &str) -> Result<(), Error> {
+        writeln!(self.output, "{}", message).map_err(Error::Prompt)
+    }
+}
+
+/// `1) rest, 2) grpc`
+fn numbered(choices: &[String]) -> String {
+    choices
+        .iter()
+        .enumerate()
+        .map(|(i, choice)| format!("{}) {}", i + 1, choice))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Resolve an answer given either as a 1-based index or a choice name.
+fn pick<'a>(answer: &str, choices: &'a [String]) -> Option<&'a String> {
+    match answer.parse::<usize>() {
+        Ok(index) if index >= 1 => choices.get(index - 1),
+        _ => choices.iter().find(|choice| choice.as_str() == answer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompter(input: &str) -> LinePrompter<&[u8], Vec<u8>> {
+        LinePrompter::new(input.as_bytes(), Vec::new())
+    }
+
+    #[test]
+    fn empty_answers_take_the_default() {
+        let mut prompter = prompter("\n  \n");
+        assert_eq!(prompter.ask("Name", Some("demo")).unwrap(), "demo");
+        assert_eq!(prompter.ask("Name", None).unwrap(), "");
+        let output = String::from_utf8(prompter.output).unwrap();
+        assert_eq!(output, "? Name [demo]: ? Name: ");
+    }
+
+    #[test]
+    fn end_of_input_is_an_error() {
+        let mut prompter = prompter("yes\n");
+        assert!(prompter.confirm("Sure?", false).unwrap());
+        let error = prompter.ask("Name", Some("demo")).unwrap_err();
+        assert!(
+            matches!(&error, Error::Prompt(e) if e.kind() == io::ErrorKind::UnexpectedEof),
+            "{:?}",
+            error
+        );
+    }
+
+    #[test]
+    fn unclear_answers_are_asked_again() {
+        let mut prompter = prompter("\ndemo\nmaybe\nN\n");
+        assert_eq!(prompter.input("Name", None).unwrap(), "demo");
+        assert!(!prompter.confirm("Sure?", true).unwrap());
+        let output = String::from_utf8(prompter.output).unwrap();
+        assert!(output.contains("A value is required."), "{}", output);
+        assert!(output.contains("Please answer yes or no."), "{}", output);
+    }
+
+    #[test]
+    fn choices_are_picked_by_number_or_name() {
+        let choices: Vec<String> = ["rest", "grpc", "mixed"].map(String::from).to_vec();
+        let mut prompter = prompter("2\nmixed\n4\n\n");
+        let mut select = || prompter.select("Template", &choices, "rest").unwrap();
+        assert_eq!(select(), "grpc");
+        assert_eq!(select(), "mixed");
+        assert_eq!(select(), "rest");
+        let output = String::from_utf8(prompter.output).unwrap();
+        assert!(output.contains("'4' is not"), "{}", output);
+    }
+
+    #[test]
+    fn multi_select_takes_a_list_or_none() {
+        let choices: Vec<String> = ["auth", "cache"].map(String::from).to_vec();
+        let defaults = vec!["auth".to_string()];
+        let mut prompter = prompter("\n2, auth, 2\nNONE\n");
+        let mut pick = || {
+            prompter
+                .multi_select("Features", &choices, &defaults)
+                .unwrap()
+        };
+        assert_eq!(pick(), ["auth"]);
+        assert_eq!(pick(), ["cache", "auth"]);
+        assert!(pick().is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::manifest::MANIFEST_FILE;
+use crate::template::{self, TemplateFile};
+
+/// `$XDG_CONFIG_HOME/create_woragis`, falling back to
+/// `~/.config/create_woragis`.
+pub fn config_dir() -> Option<PathBuf> {
+    base_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// `$XDG_CACHE_HOME/create_woragis`, falling back to
+/// `~/.cache/create_woragis`.
+pub fn cache_dir() -> Option<PathBuf> {
+    base_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// `$<var>/create_woragis`, or `~/<fallback>/create_woragis` when the
+/// variable is unset.
+fn base_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    let base = match std::env::var_os(var) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => {
+            let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+            PathBuf::from(home).join(fallback)
+        }
+    };
+    Some(base.join("create_woragis"))
+}
+
+/// The user template registry: one template per subdirectory.
+pub fn templates_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("templates"))
+}
+
+/// Name and directory of every template in the user registry, i.e. each
+/// subdirectory holding a manifest. A missing registry is simply empty.
+pub fn templates() -> Result<Vec<(String, PathBuf)>, Error> {
+    let Some(root) = templates_dir() else {
+        return Ok(Vec::new());
+    };
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut found = Vec::new();
+    for entry in fs::read_dir(&root).map_err(|e| Error::io(&root, e))? {
+        let entry = entry.map_err(|e| Error::io(&root, e))?;
+        let dir = entry.path();
+        if dir.join(MANIFEST_FILE).is_file() {
+            found.push((entry.file_name().to_string_lossy().into_owned(), dir));
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Read every file below `root` into memory, with paths relative to it.
+///
+/// `.git` directories are skipped so a template can live in its own
+/// repository, and so are build artifacts left by building it in place.
+pub fn read_files(root: &Path) -> Result<Vec<TemplateFile>, Error> {
+    let mut files = Vec::new();
+    read_into(root, Path::new(""), &mut files)?;
+    Ok(files)
+}
+
+fn read_into(root: &Path, relative: &Path, files: &mut Vec<TemplateFile>) -> Result<(), Error> {
+    let dir = root.join(relative);
+    let mut entries = fs::read_dir(&dir)
+        .map_err(|e| Error::io(&dir, e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Error::io(&dir, e))?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name();
+        if name == ".git" || template::is_build_artifact(&name) {
+            continue;
+        }
+        let path = relative.join(entry.file_name());
+        let full = entry.path();
+        if full.is_dir() {
+            read_into(root, &path, files)?;
+        } else {
+            let contents = fs::read(&full).map_err(|e| Error::io(&full, e))?;
+            files.push(TemplateFile {
+                path,
+                contents: Cow::Owned(contents),
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::git;
+use crate::registry;
+use crate::summary::sha256;
+
+/// A template in a git repository, written `git+<url>[#<ref>]`.
+///
+/// `<ref>` may be a tag, a branch or a (possibly abbreviated) commit and
+/// defaults to the repository's `HEAD`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitSource {
+    pub url: String,
+    pub reference: Option<String>,
+}
+
+/// A commit of a [`GitSource`], checked out in the cache.
+pub struct Checkout {
+    pub dir: PathBuf,
+    pub commit: String,
+}
+
+impl GitSource {
+    /// Parse `raw`, or `None` when it is not a `git+` URL.
+    pub fn parse(raw: &str) -> Option<GitSource> {
+        let rest = raw.strip_prefix("git+")?;
+        let (url, reference) = match rest.rsplit_once('#') {
+            Some((url, reference)) if !reference.is_empty() => (url, Some(reference.to_string())),
+            Some((url, _)) => (url, None),
+            None => (rest, None),
+        };
+        Some(GitSource {
+            url: url.to_string(),
+            reference,
+        })
+    }
+
+    /// Template name derived from the repository, e.g. `api` for
+    /// `file:///srv/api.git`.
+    pub fn name(&self) -> String {
+        let last = self
+            .url
+            .trim_end_matches('/')
+            .rsplit(['/', ':'])
+            .next()
+            .unwrap_or_default();
+        last.strip_suffix(".git").unwrap_or(last).to_string()
+    }
+
+    /// Fetch the repository into the cache and check out the pinned commit.
+    ///
+    /// Each repository is mirrored once under the cache directory and only
+    /// fetched again when the ref is not a full commit that is already known.
+    /// If that fetch fails but a mirror exists, the cached refs are used, so
+    /// a previously used template keeps working offline.
+    pub fn checkout(&self) -> Result<Checkout, Error> {
+        let cache = registry::cache_dir()
+            .ok_or_else(|| Error::UnknownTemplate("cannot locate a cache directory".to_string()))?
+            .join("git")
+            .join(cache_key(&self.url));
+        fs::create_dir_all(&cache).map_err(|e| Error::io(&cache, e))?;
+
+        let mirror = cache.join("repo.git");
+        let reference = self.reference.as_deref().unwrap_or("HEAD");
+        if !mirror.exists() {
+            let tmp = cache.join(format!(".repo.git.tmp-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&tmp);
+            let cloned = git::run(
+                &cache,
+                &[
+                    "clone",
+                    "--quiet",
+                    "--mirror",
+                    // A URL from a spec file must not pass for an option
+                    "--",
+                    &self.url,
+                    &tmp.to_string_lossy(),
+                ],
+            );
+            if let Err(e) = cloned {
+                let _ = fs::remove_dir_all(&tmp);
+                return Err(e);
+            }
+            fs::rename(&tmp, &mirror).map_err(|e| Error::io(&mirror, e))?;
+        } else if !(is_full_commit(reference) && resolve(&mirror, reference).is_ok())
+            && let Err(e) = git::run(&mirror, &["fetch", "--quiet", "--prune", "origin"])
+        {
+            eprintln!(
+                "⚠️  Could not update '{}', using the cached copy: {}",
+                self.url, e
+            );
+        }
+
+        let commit = resolve(&mirror, reference).map_err(|_| {
+            Error::UnknownTemplate(format!(
+                "'{}' has no tag, branch or commit named '{}'",
+                self.url, reference
+            ))
+        })?;
+
+        let dir = cache.join(&commit);
+        if !dir.exists() {
+            let tmp = cache.join(format!(".{}.tmp-{}", commit, std::process::id()));
+            let _ = fs::remove_dir_all(&tmp);
+            fs::create_dir(&tmp).map_err(|e| Error::io(&tmp, e))?;
+            let work_tree = tmp.to_string_lossy().into_owned();
+            let checked_out = git::run(
+                &mirror,
+                &[
+                    "--work-tree",
+                    &work_tree,
+                    "checkout",
+                    "--quiet",
+                    "--force",
+                    &commit,
+                    "--",
+                    ".",
+                ],
+            );
+            if let Err(e) = checked_out {
+                let _ = fs::remove_dir_all(&tmp);
+                return Err(e);
+            }
+            fs::rename(&tmp, &dir).map_err(|e| Error::io(&dir, e))?;
+        }
+
+        Ok(Checkout { dir, commit })
+    }
+}
+
+impl fmt::Display for GitSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "git+{}", self.url)?;
+        if let Some(reference) = &self.reference {
+            write!(f, "#{}", reference)?;
+        }
+        Ok(())
+    }
+}
+
+/// The full commit `reference` points to in `repo`.
+fn resolve(repo: &Path, reference: &str) -> Result<String, Error> {
+    let spec = format!("{}^{{commit}}", reference);
+    let out = git::run(
+        repo,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            "--end-of-options",
+            &spec,
+        ],
+    )?;
+    Ok(out.trim().to_string())
+}
+
+fn is_full_commit(reference: &str) -> bool {
+    reference.len() == 40 && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// A directory name identifying `url` in the cache: its SHA-256, so URLs
+/// that differ only in punctuation get mirrors of their own.
+///
+/// Refs share the mirror; their checkouts are kept apart by commit.
+fn cache_key(url: &str) -> String {
+    sha256(url.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_keys_keep_urls_apart() {
+        let keys = [
+            cache_key("https://example.com/a/b.git"),
+            cache_key("https://example.com/a_b.git"),
+            cache_key("https://example.com/a:b.git"),
+        ];
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(key.len(), 64, "{}", key);
+            assert!(!keys[i + 1..].contains(key), "{:?}", keys);
+        }
+        assert_eq!(keys[0], cache_key("https://example.com/a/b.git"));
+    }
+}
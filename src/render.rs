@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use minijinja::{Environment, UndefinedBehavior, Value};
+
+use crate::case;
+use crate::manifest::{Extra, VarValue};
+use crate::workspace::Workspace;
+
+/// Variables available to templates while rendering.
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    vars: BTreeMap<String, Value>,
+}
+
+impl Context {
+    /// Build the base context for a project.
+    ///
+    /// `project_name` is kept as typed by the user, `crate_name` is the
+    /// snake_case form Cargo will use for the package and binary.
+    pub fn new(project_name: &str, author: &str) -> Self {
+        let mut context = Context::default();
+        context.insert("project_name", project_name);
+        context.insert("crate_name", case::snake_case(project_name));
+        context.insert("author", author);
+        context.set_services(&[case::snake_case(project_name)], &[]);
+        context.set_workspace(None);
+        context
+    }
+
+    /// Add the template's variables and a `with_ci`/`with_infra` flag for
+    /// each extra.
+    pub fn set_options(&mut self, variables: &BTreeMap<String, VarValue>, extras: &[Extra]) {
+        self.extend(variables.clone());
+        self.insert("with_ci", extras.contains(&Extra::Ci));
+        self.insert("with_infra", extras.contains(&Extra::Infra));
+    }
+
+    /// Add a `workspace` flag, and a `worker` one for its worker crate; a
+    /// workspace's services are its binary crates.
+    pub fn set_workspace(&mut self, workspace: Option<&Workspace>) {
+        self.insert("workspace", workspace.is_some());
+        self.insert("worker", workspace.is_some_and(|w| w.worker));
+        if let Some(workspace) = workspace {
+            self.set_services(&workspace.services(), &workspace.workers());
+        }
+    }
+
+    /// Set `services`, the packages the CI builds and Terraform deploys,
+    /// just the project's own crate by default, and `workers`, those of them
+    /// that serve nothing and so get no port.
+    pub fn set_services(&mut self, services: &[String], workers: &[String]) {
+        self.insert("services", services.to_vec());
+        self.insert("workers", workers.to_vec());
+    }
+
+    pub fn insert(&mut self, key: &str, value: impl Into<Value>) {
+        self.vars.insert(key.to_string(), value.into());
+    }
+
+    pub fn extend<V: Into<Value>>(&mut self, vars: impl IntoIterator<Item = (String, V)>) {
+        self.vars
+            .extend(vars.into_iter().map(|(key, value)| (key, value.into())));
+    }
+}
+
+/// Renders file contents and paths with `{{ placeholder }}` substitution.
+///
+/// Besides plain variables, the `snake_case`, `kebab_case` and `pascal_case`
+/// filters are available, e.g. `{{ project_name | pascal_case }}`, and
+/// `toml` quotes a value for use inside a TOML file.
+pub struct Renderer {
+    env: Environment<'static>,
+    context: Value,
+}
+
+impl Renderer {
+    pub fn new(context: &Context) -> Self {
+        let mut env = Environment::new();
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        env.set_keep_trailing_newline(true);
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.add_filter("snake_case", |s: &str| case::snake_case(s));
+        env.add_filter("kebab_case", |s: &str| case::kebab_case(s));
+        env.add_filter("pascal_case", |s: &str| case::pascal_case(s));
+        env.add_filter("toml", toml_literal);
+
+        Renderer {
+            env,
+            context: Value::from(context.vars.clone()),
+        }
+    }
+
+    /// Render a template string.
+    pub fn render_str(&self, source: &str) -> Result<String, minijinja::Error> {
+        self.env.render_str(source, &self.context)
+    }
+
+    /// Evaluate a condition such as `docker and database == "postgres"`.
+    pub fn eval(&self, expr: &str) -> Result<bool, minijinja::Error> {
+        let expr = self.env.compile_expression(expr)?;
+        Ok(expr.eval(&self.context)?.is_true())
+    }
+
+    /// Render file contents. Anything that is not UTF-8 is passed through
+    /// untouched so binary assets survive.
+    pub fn render_bytes(&self, contents: &[u8]) -> Result<Vec<u8>, minijinja::Error> {
+        match std::str::from_utf8(contents) {
+            Ok(source) => self.render_str(source).map(String::into_bytes),
+            Err(_) => Ok(contents.to_vec()),
+        }
+    }
+
+    /// Render every component of a relative path, so files and directories
+    /// can be named after the project (e.g. `src/{{ crate_name }}.rs`).
+    pub fn render_path(&self, path: &Path) -> Result<PathBuf, minijinja::Error> {
+        let mut rendered = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(name) => match name.to_str() {
+                    Some(name) => rendered.push(self.render_str(name)?),
+                    None => rendered.push(name),
+                },
+                other => rendered.push(other),
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+/// Format a value as a TOML literal, e.g. `Says "hi"` -> `"Says \"hi\""`.
+fn toml_literal(value: Value) -> Result<String, minijinja::Error> {
+    toml::Value::try_from(&value)
+        .map(|value| value.to_string())
+        .map_err(|e| minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, e.to_string()))
+}
@@ -0,0 +1,447 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml_edit::{DocumentMut, Item};
+
+use crate::case;
+use crate::error::Error;
+use crate::generate::{self, Outcome, Report};
+use crate::lock::{self, Lock, Snapshot};
+use crate::manifest::Extra;
+use crate::plan::{self, Plan};
+use crate::prompt::LinePrompter;
+use crate::render::{Context, Renderer};
+use crate::template::Template;
+use crate::upgrade::{self, Merged};
+use crate::workspace::{self, Unified};
+
+/// A crate `new-service` added to a workspace.
+pub struct Service {
+    /// The workspace root.
+    pub root: PathBuf,
+    /// The crate's directory, relative to `root`.
+    pub dir: PathBuf,
+    /// Whether the crate was appended to `[workspace] members` (rather than
+    /// already matched by a glob).
+    pub listed: bool,
+    /// Paths relative to `root`.
+    pub report: Report,
+    pub unified: Vec<Unified>,
+}
+
+/// `new-service`: generate `template` as a new member named `name` of the
+/// Cargo workspace at or above `dir`.
+///
+/// The crate goes next to the existing members (`crates/<name>` in one
+/// generated with `--workspace`), gets its own lock file so `generate` and
+/// `upgrade` work on it, and inherits its dependencies from
+/// `[workspace.dependencies]`. `.github/` and `terraform/`, when present,
+/// get the new service merged in the way `add` merges files.
+pub fn new_service(
+    dir: &Path,
+    name: &str,
+    template: &Template,
+    overrides: &[(String, String)],
+    author: &str,
+) -> Result<Service, Error> {
+    crate::name::validate(name).map_err(Error::InvalidName)?;
+    let (root, mut cargo) = find_root(dir)?;
+    let members = members(&root, &cargo);
+    let crate_name = case::snake_case(name);
+    if members
+        .iter()
+        .any(|member| case::snake_case(&member.name) == crate_name)
+    {
+        return Err(Error::Project(format!(
+            "The workspace already has a crate named '{}'",
+            crate_name
+        )));
+    }
+
+    let patterns = patterns(&cargo);
+    let parent = parent_dir(&patterns);
+    let service_dir = parent.join(name);
+    let full_dir = root.join(&service_dir);
+    if full_dir.exists() {
+        return Err(Error::DirectoryExists(full_dir));
+    }
+
+    let manifest = &template.manifest;
+    let variables = manifest.resolve_variables(overrides)?;
+    let mut context = Context::new(name, author);
+    context.set_options(&variables, &[]);
+    let mut plan = Plan::build(template, &[], None, &Renderer::new(&context))?;
+
+    let workspace = cargo["workspace"]
+        .as_table_like_mut()
+        .expect("find_root only returns workspaces");
+    let declared = workspace.contains_key("dependencies");
+    let dependencies = workspace
+        .entry("dependencies")
+        .or_insert_with(toml_edit::table)
+        .as_table_mut()
+        .ok_or_else(|| {
+            Error::Project("[workspace.dependencies] of the workspace is not a table".to_string())
+        })?;
+    let mut unified = Vec::new();
+    let mut binary = false;
+    for file in &mut plan.files {
+        binary |= file.path == Path::new("src/main.rs");
+        if file.path != Path::new("Cargo.toml") {
+            continue;
+        }
+        let text = String::from_utf8_lossy(&file.contents);
+        let mut member: DocumentMut = text.parse().map_err(|e| Error::Render {
+            path: template.root().join("Cargo.toml"),
+            message: format!("{}", e),
+        })?;
+        unified = workspace::inherit_dependencies(&mut member, dependencies);
+        file.contents = member.to_string().into_bytes();
+    }
+    if !declared && dependencies.is_empty() {
+        workspace.remove("dependencies");
+    }
+
+    let glob = format!("{}/*", parent.display());
+    let listed = !patterns.contains(&glob);
+    if listed {
+        let path = service_dir.to_string_lossy().replace('\\', "/");
+        match workspace.get_mut("members").and_then(Item::as_array_mut) {
+            Some(array) => array.push(path),
+            None => {
+                workspace.insert(
+                    "members",
+                    toml_edit::value(toml_edit::Array::from_iter([path])),
+                );
+            }
+        }
+    }
+
+    let lock = Lock::new(template, name, author, &variables, &[], None);
+    plan.add(lock::LOCK_FILE, lock.to_toml());
+    let snapshot = Snapshot::of(&plan);
+    plan.add(lock::BASE_FILE, snapshot.to_toml());
+
+    let mut report = Report::default();
+    let generated = generate::generate(
+        &plan,
+        &full_dir,
+        None,
+        &mut LinePrompter::stdio(),
+        &mut |_| Ok(()),
+    )?;
+    for (path, outcome) in generated.entries {
+        report.entries.push((service_dir.join(path), outcome));
+    }
+
+    let cargo_path = root.join("Cargo.toml");
+    let text = cargo.to_string();
+    if fs::read_to_string(&cargo_path).ok().as_deref() != Some(text.as_str()) {
+        fs::write(&cargo_path, text).map_err(|e| Error::io(&cargo_path, e))?;
+        report
+            .entries
+            .push((PathBuf::from("Cargo.toml"), Outcome::Updated));
+    }
+
+    let services: Vec<String> = members
+        .iter()
+        .filter(|member| member.binary)
+        .map(|member| member.name.clone())
+        .collect();
+    let mut with = services.clone();
+    if binary {
+        with.push(crate_name);
+    }
+    report
+        .entries
+        .extend(patch_extras(&root, author, &services, &with)?);
+
+    Ok(Service {
+        root,
+        dir: service_dir,
+        listed,
+        report,
+        unified,
+    })
+}
+
+/// A member crate of a workspace.
+struct Member {
+    /// The package name, as `--package` takes it.
+    name: String,
+    /// Whether it builds a binary, i.e. is a service.
+    binary: bool,
+}
+
+/// The closest directory at or above `dir` whose `Cargo.toml` has a
+/// `[workspace]`, with that manifest.
+fn find_root(dir: &Path) -> Result<(PathBuf, DocumentMut), Error> {
+    let start = fs::canonicalize(dir).map_err(|e| Error::io(dir, e))?;
+    for candidate in start.ancestors() {
+        let path = candidate.join("Cargo.toml");
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let cargo: DocumentMut = text
+            .parse()
+            .map_err(|e| Error::Project(format!("{}: {}", path.display(), e)))?;
+        if cargo.get("workspace").is_some_and(Item::is_table_like) {
+            return Ok((candidate.to_path_buf(), cargo));
+        }
+    }
+    Err(Error::Project(format!(
+        "No Cargo workspace in or above '{}'; run this inside one (see --workspace)",
+        dir.display()
+    )))
+}
+
+/// The `[workspace] members` patterns as written.
+fn patterns(cargo: &DocumentMut) -> Vec<String> {
+    cargo["workspace"]
+        .get("members")
+        .and_then(Item::as_array)
+        .map(|members| {
+            members
+                .iter()
+                .filter_map(|member| member.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Every member below `root`, in the order of `[workspace] members`, with
+/// `dir/*` globs expanded.
+fn members(root: &Path, cargo: &DocumentMut) -> Vec<Member> {
+    let mut dirs = Vec::new();
+    for pattern in patterns(cargo) {
+        match pattern.strip_suffix("/*") {
+            Some(parent) => {
+                let mut found: Vec<PathBuf> = fs::read_dir(root.join(parent))
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.join("Cargo.toml").is_file())
+                    .collect();
+                found.sort();
+                dirs.extend(found);
+            }
+            None => dirs.push(root.join(pattern)),
+        }
+    }
+
+    dirs.into_iter()
+        .filter_map(|dir| {
+            let text = fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+            let cargo: DocumentMut = text.parse().ok()?;
+            let name = cargo.get("package")?.get("name")?.as_str()?;
+            Some(Member {
+                name: name.to_string(),
+                binary: dir.join("src/main.rs").is_file() || cargo.contains_key("bin"),
+            })
+        })
+        .collect()
+}
+
+/// Where new members go: the directory most existing members share (`crates`
+/// for `crates/api` and `crates/*`), or the root.
+fn parent_dir(patterns: &[String]) -> PathBuf {
+    let mut counts: Vec<(PathBuf, usize)> = Vec::new();
+    for pattern in patterns {
+        let parent = match pattern.strip_suffix("/*") {
+            Some(parent) => PathBuf::from(parent),
+            None => Path::new(pattern)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        };
+        match counts.iter_mut().find(|(dir, _)| *dir == parent) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((parent, 1)),
+        }
+    }
+    // The first of the most common, so ties go to the earliest member
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(dir, _)| dir.clone())
+        .unwrap_or_default()
+}
+
+/// Add the new service to the extras the workspace has: render them with the
+/// `before` and `after` lists of services and merge the difference into the
+/// files as `add` does.
+fn patch_extras(
+    root: &Path,
+    author: &str,
+    before: &[String],
+    after: &[String],
+) -> Result<Vec<(PathBuf, Outcome)>, Error> {
+    let extras: Vec<Extra> = [Extra::Ci, Extra::Infra]
+        .into_iter()
+        .filter(|extra| root.join(extra.target_dir()).is_dir())
+        .collect();
+    if extras.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let lock = Lock::read(root)?;
+    let name = match &lock {
+        Some(lock) => lock.project.name.clone(),
+        None => root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let workers = lock
+        .and_then(|lock| lock.project.workspace)
+        .map(|workspace| workspace.workers())
+        .unwrap_or_default();
+    let render = |services: &[String]| {
+        let mut context = Context::new(&name, author);
+        context.set_options(&BTreeMap::new(), &extras);
+        context.set_services(services, &workers);
+        plan::render_extras(&extras, &Renderer::new(&context))
+    };
+    let (old, new) = (render(before)?, render(after)?);
+
+    let mut entries = Vec::new();
+    for (previous, file) in old.iter().zip(&new) {
+        let path = root.join(&file.path);
+        // Left alone if the user deleted it
+        let Ok(existing) = fs::read(&path) else {
+            continue;
+        };
+        let (contents, outcome) =
+            match upgrade::merge(&previous.contents, &existing, &file.contents) {
+                Merged::Clean(merged) if merged == existing => continue,
+                Merged::Clean(merged) => (merged, Outcome::Updated),
+                Merged::Conflict(conflicted) => (conflicted, Outcome::Conflict),
+                Merged::Binary => continue,
+            };
+        fs::write(&path, contents).map_err(|e| Error::io(&path, e))?;
+        entries.push((file.path.clone(), outcome));
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory of its own below the system temp directory.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join("create_woragis-tests")
+            .join(format!("service-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn patterns(members: &[&str]) -> Vec<String> {
+        members.iter().map(|member| member.to_string()).collect()
+    }
+
+    #[test]
+    fn new_members_go_where_most_existing_ones_are() {
+        assert_eq!(
+            parent_dir(&patterns(&["crates/api", "crates/*"])),
+            Path::new("crates")
+        );
+        assert_eq!(
+            parent_dir(&patterns(&["apps/web", "services/a", "services/b"])),
+            Path::new("services")
+        );
+        assert_eq!(
+            parent_dir(&patterns(&["api", "crates/shared"])),
+            Path::new("")
+        );
+        assert_eq!(parent_dir(&[]), Path::new(""));
+    }
+
+    #[test]
+    fn members_expand_globs_and_tell_services_apart() {
+        let root = scratch("members");
+        write(
+            &root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\", \"tools/cli\"]\n",
+        );
+        write(
+            &root.join("crates/api/Cargo.toml"),
+            "[package]\nname = \"api\"\n",
+        );
+        write(&root.join("crates/api/src/main.rs"), "fn main() {}\n");
+        write(
+            &root.join("crates/shared/Cargo.toml"),
+            "[package]\nname = \"shared\"\n",
+        );
+        write(&root.join("crates/shared/src/lib.rs"), "");
+        // Not a crate, so not a member
+        write(&root.join("crates/notes/README.md"), "");
+        write(
+            &root.join("tools/cli/Cargo.toml"),
+            "[package]\nname = \"my-cli\"\n\n[[bin]]\nname = \"cli\"\npath = \"cli.rs\"\n",
+        );
+
+        let (found, cargo) = find_root(&root.join("crates/api/src")).unwrap();
+        assert_eq!(found, fs::canonicalize(&root).unwrap());
+        let members: Vec<(String, bool)> = members(&found, &cargo)
+            .into_iter()
+            .map(|member| (member.name, member.binary))
+            .collect();
+        assert_eq!(
+            members,
+            [
+                ("api".to_string(), true),
+                ("shared".to_string(), false),
+                ("my-cli".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_new_service_is_merged_into_the_ci() {
+        let root = scratch("extras");
+        let services = ["api".to_string()];
+        let mut context = Context::new("demo", "Test");
+        context.set_options(&BTreeMap::new(), &[Extra::Ci]);
+        context.set_services(&services, &[]);
+        let rendered = plan::render_extras(&[Extra::Ci], &Renderer::new(&context)).unwrap();
+        let ci = &rendered[0];
+        // The user's own step survives the merge
+        let edited = String::from_utf8(ci.contents.clone()).unwrap().replace(
+            "      - run: cargo test --workspace\n",
+            "      - run: cargo test --workspace\n      - run: cargo doc --no-deps\n",
+        );
+        write(&root.join(&ci.path), &edited);
+
+        let entries = patch_extras(
+            &root,
+            "Test",
+            &services,
+            &["api".to_string(), "billing".to_string()],
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, ci.path);
+        assert!(matches!(entries[0].1, Outcome::Updated));
+        let patched = fs::read_to_string(root.join(&ci.path)).unwrap();
+        assert!(
+            patched.contains("          - api\n          - billing\n"),
+            "{}",
+            patched
+        );
+        assert!(patched.contains("cargo doc --no-deps"), "{}", patched);
+
+        // Nothing to do when the services are unchanged
+        let entries = patch_extras(&root, "Test", &services, &services).unwrap();
+        assert!(entries.is_empty());
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use jsonschema::error::ValidationErrorKind;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config::Settings;
+use crate::error::Error;
+use crate::generator::Generator;
+use crate::manifest::VarValue;
+use crate::template::Template;
+
+/// JSON Schema every spec is validated against, printed by `schema`.
+pub const SCHEMA: &str = include_str!("../schemas/project-spec.schema.json");
+
+/// Generator run for resources that do not name one.
+const DEFAULT_GENERATOR: &str = "resource";
+
+/// A whole project described in a file, for `--from`.
+///
+/// ```yaml
+/// name: billing
+/// template: rest
+/// features: [auth, cache]
+/// database: postgres
+/// resources:
+///   - name: invoice
+///     fields: ["amount:float", "due:datetime?"]
+/// ci: true
+/// env:
+///   RUST_LOG: debug
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Spec {
+    pub name: String,
+    pub template: Option<String>,
+    pub author: Option<String>,
+    pub license: Option<String>,
+    pub database: Option<String>,
+    pub framework: Option<String>,
+    pub features: Option<Vec<String>>,
+    #[serde(default)]
+    pub vars: BTreeMap<String, VarValue>,
+    #[serde(default)]
+    pub resources: Vec<Resource>,
+    pub ci: Option<bool>,
+    pub infra: Option<bool>,
+    /// Values for `.env.example`; numbers and booleans are written as is.
+    #[serde(default)]
+    pub env: BTreeMap<String, EnvValue>,
+}
+
+/// A resource generated into the project as part of creating it.
+#[derive(Debug, Deserialize)]
+pub struct Resource {
+    pub name: String,
+    pub generator: Option<String>,
+    /// `NAME:TYPE` pairs, as given to `generate`.
+    #[serde(default)]
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum EnvValue {
+    Bool(bool),
+    Number(serde_json::Number),
+    String(String),
+}
+
+impl Spec {
+    /// Read a spec from a `.yaml`/`.yml`, `.json` or `.toml` file and
+    /// validate it against [`SCHEMA`].
+    pub fn read(path: &Path) -> Result<Spec, Error> {
+        let source = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        let invalid = |errors: Vec<String>| Error::Spec {
+            path: path.to_path_buf(),
+            errors,
+        };
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let value: Value = match extension {
+            "yaml" | "yml" => serde_yaml_ng::from_str(&source).map_err(|e| e.to_string()),
+            "json" => serde_json::from_str(&source).map_err(|e| e.to_string()),
+            "toml" => toml::from_str(&source).map_err(|e| e.to_string()),
+            _ => Err(format!(
+                "unknown format '{}' (expected .yaml, .yml, .json or .toml)",
+                extension
+            )),
+        }
+        .map_err(|e| invalid(vec![e]))?;
+
+        let schema: Value = serde_json::from_str(SCHEMA).expect("the spec schema is valid JSON");
+        let validator = jsonschema::validator_for(&schema).expect("the spec schema is valid");
+        let mut errors = Vec::new();
+        for error in validator.iter_errors(&value) {
+            let parent = key(error.instance_path().as_str());
+            match error.kind() {
+                // Point at the misspelled key itself rather than its parent
+                ValidationErrorKind::AdditionalProperties { unexpected } => {
+                    for name in unexpected {
+                        let key = if parent.is_empty() {
+                            name.clone()
+                        } else {
+                            format!("{}.{}", parent, name)
+                        };
+                        errors.push(at(&key, "unknown key"));
+                    }
+                }
+                _ => errors.push(at(&parent, &error.to_string())),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(invalid(errors));
+        }
+        serde_json::from_value(value).map_err(|e| invalid(vec![e.to_string()]))
+    }
+
+    /// Check the spec against `template`: variables take values it allows
+    /// and resources name generators and field types it has.
+    pub fn check(&self, template: &Template, path: &Path) -> Result<(), Error> {
+        let manifest = &template.manifest;
+        let mut errors = Vec::new();
+        let named = [
+            ("database", self.database.clone()),
+            ("framework", self.framework.clone()),
+            ("features", self.features.as_ref().map(|f| f.join(","))),
+        ];
+        let named = named
+            .into_iter()
+            .filter_map(|(name, value)| Some((name.to_string(), name.to_string(), value?)));
+        let vars = self
+            .vars
+            .iter()
+            .map(|(name, value)| (format!("vars.{}", name), name.clone(), value.to_string()));
+        for (key, name, raw) in named.chain(vars) {
+            match manifest.variables.iter().find(|v| v.name == name) {
+                Some(variable) => {
+                    if let Err(e) = variable.parse(&raw) {
+                        errors.push(at(&key, &e));
+                    }
+                }
+                None => errors.push(at(
+                    &key,
+                    &format!("template '{}' has no '{}' variable", template.name, name),
+                )),
+            }
+        }
+
+        for (i, resource) in self.resources.iter().enumerate() {
+            match Generator::find(template, resource.generator()) {
+                Ok(generator) => {
+                    if let Err(e) = generator.parse_fields(&resource.fields) {
+                        errors.push(at(&format!("resources[{}].fields", i), &e.to_string()));
+                    }
+                }
+                Err(e) => errors.push(at(&format!("resources[{}].generator", i), &e.to_string())),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Spec {
+                path: path.to_path_buf(),
+                errors,
+            })
+        }
+    }
+
+    /// The spec's options, to layer over the config's.
+    pub fn settings(&self) -> Settings {
+        Settings {
+            author: self.author.clone(),
+            license: self.license.clone(),
+            template: self.template.clone(),
+            database: self.database.clone(),
+            framework: self.framework.clone(),
+            features: self.features.clone(),
+            ci: self.ci,
+            infra: self.infra,
+            vars: self.vars.clone(),
+        }
+    }
+
+    /// `KEY=VALUE` pairs for `.env.example`.
+    pub fn env(&self) -> Vec<(String, String)> {
+        self.env
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    EnvValue::Bool(b) => b.to_string(),
+                    EnvValue::Number(n) => n.to_string(),
+                    EnvValue::String(s) => s.clone(),
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    }
+}
+
+impl Resource {
+    pub fn generator(&self) -> &str {
+        self.generator.as_deref().unwrap_or(DEFAULT_GENERATOR)
+    }
+}
+
+/// `at `resources[0].fields`: message`, or without the key at the root.
+fn at(key: &str, message: &str) -> String {
+    if key.is_empty() {
+        message.to_string()
+    } else {
+        format!("at `{}`: {}", key, message)
+    }
+}
+
+/// `resources[0].fields` for the JSON pointer `/resources/0/fields`.
+fn key(pointer: &str) -> String {
+    let mut key = String::new();
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        if segment.parse::<usize>().is_ok() {
+            key.push_str(&format!("[{}]", segment));
+        } else {
+            if !key.is_empty() {
+                key.push('.');
+            }
+            key.push_str(&segment);
+        }
+    }
+    key
+}
@@ -0,0 +1,346 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::Error;
+use crate::generate::{Outcome, Report};
+use crate::lock::{Lock, LockedTemplate};
+use crate::manifest::{Extra, VarValue};
+use crate::plan::Plan;
+use crate::template::Template;
+use crate::workspace::Workspace;
+
+/// What `--json` prints once a project is created (or planned, with
+/// `--dry-run`).
+///
+/// ```json
+/// {
+///   "project": "billing",
+///   "path": "billing",
+///   "dry_run": false,
+///   "template": { "name": "rest", "version": "0.4.0" },
+///   "options": { "database": "sqlite", "features": ["auth"] },
+///   "extras": ["ci"],
+///   "skipped_extras": ["infra"],
+///   "files": [{ "path": "Cargo.toml", "outcome": "created", "size": 812, "sha256": "…" }],
+///   "resources": [{ "generator": "resource", "name": "invoice" }],
+///   "hints": [{ "kind": "env", "name": "SECRET_KEY", "example": "change-me" }]
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub project: String,
+    pub path: PathBuf,
+    pub dry_run: bool,
+    pub template: LockedTemplate,
+    pub options: BTreeMap<String, VarValue>,
+    pub extras: Vec<Extra>,
+    /// Extras the template supports that were not requested.
+    pub skipped_extras: Vec<Extra>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<Workspace>,
+    pub files: Vec<FileEntry>,
+    pub resources: Vec<GeneratedResource>,
+    pub hints: Vec<Hint>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileEntry {
+    pub path: PathBuf,
+    /// `created`, `skipped`, … (see [`Outcome::name`]); `planned` on a dry run.
+    pub outcome: &'static str,
+    pub size: u64,
+    /// Hex SHA-256 of the file's contents.
+    pub sha256: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GeneratedResource {
+    pub generator: String,
+    pub name: String,
+}
+
+/// Something left for the user to do after generation.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Hint {
+    /// A variable the project reads from `.env`, with the sample value from
+    /// `.env.example`.
+    Env { name: String, example: String },
+    /// A line a generator could not insert because its anchor is gone.
+    ManualInsert { file: PathBuf, line: String },
+}
+
+impl Summary {
+    pub fn new(lock: &Lock, template: &Template, dir: &Path, dry_run: bool) -> Summary {
+        let extras = lock.project.extras.clone();
+        let skipped_extras = template
+            .manifest
+            .template
+            .extras
+            .iter()
+            .copied()
+            .filter(|extra| !extras.contains(extra))
+            .collect();
+        Summary {
+            project: lock.project.name.clone(),
+            path: dir.to_path_buf(),
+            dry_run,
+            template: lock.template.clone(),
+            options: lock.options.clone(),
+            extras,
+            skipped_extras,
+            workspace: lock.project.workspace,
+            files: Vec::new(),
+            resources: Vec::new(),
+            hints: Vec::new(),
+        }
+    }
+
+    /// Record every planned file as it would be written.
+    pub fn add_planned(&mut self, plan: &Plan) {
+        for file in &plan.files {
+            self.files.push(FileEntry {
+                path: file.path.clone(),
+                outcome: "planned",
+                size: file.contents.len() as u64,
+                sha256: sha256(&file.contents),
+            });
+        }
+    }
+
+    /// Record the files of `report` as they now are below `root`.
+    pub fn add_report(&mut self, root: &Path, report: &Report) -> Result<(), Error> {
+        for (path, outcome) in &report.entries {
+            let path = match outcome {
+                Outcome::NewCopy(copy) => copy,
+                Outcome::Removed => continue,
+                _ => path,
+            };
+            let full = root.join(path);
+            let contents = fs::read(&full).map_err(|e| Error::io(&full, e))?;
+            let (size, sha256) = (contents.len() as u64, sha256(&contents));
+            // A file created by the template and then edited by a generator
+            // keeps its first outcome, with its final contents
+            match self.files.iter_mut().find(|file| &file.path == path) {
+                Some(file) => (file.size, file.sha256) = (size, sha256),
+                None => self.files.push(FileEntry {
+                    path: path.clone(),
+                    outcome: outcome.name(),
+                    size,
+                    sha256,
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Add an [`Hint::Env`] for every variable in the planned `.env.example`.
+    pub fn add_env_hints(&mut self, plan: &Plan) {
+        let Some(file) = plan
+            .files
+            .iter()
+            .find(|file| file.path == Path::new(".env.example"))
+        else {
+            return;
+        };
+        let text = String::from_utf8_lossy(&file.contents);
+        for line in text.lines().map(str::trim) {
+            if line.starts_with('#') {
+                continue;
+            }
+            if let Some((name, example)) = line.split_once('=') {
+                self.hints.push(Hint::Env {
+                    name: name.trim().to_string(),
+                    example: example.trim().to_string(),
+                });
+            }
+        }
+    }
+
+    pub fn print(&self) {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(self).expect("the summary serializes to JSON")
+        );
+    }
+}
+
+/// The SHA-256 of `contents`, in lowercase hex.
+pub fn sha256(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::PlannedFile;
+
+    const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    fn rest_summary(workspace: Option<Workspace>) -> Summary {
+        let template = Template::find(crate::DEFAULT_TEMPLATE).unwrap();
+        let options = template.manifest.resolve_variables(&[]).unwrap();
+        let lock = Lock::new(&template, "demo", "Test", &options, &[Extra::Ci], workspace);
+        Summary::new(&lock, &template, Path::new("demo"), false)
+    }
+
+    fn plan(files: &[(&str, &str)]) -> Plan {
+        Plan {
+            files: files
+                .iter()
+                .map(|(path, contents)| PlannedFile {
+                    path: PathBuf::from(path),
+                    contents: contents.as_bytes().to_vec(),
+                })
+                .collect(),
+            extras: Vec::new(),
+            conditional: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn hashes_are_lowercase_hex_sha256() {
+        assert_eq!(sha256(b""), EMPTY_SHA256);
+        assert_eq!(
+            sha256(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn serializes_the_documented_fields() {
+        let mut summary = rest_summary(None);
+        summary.add_planned(&plan(&[("Cargo.toml", "abc"), (".env.example", "")]));
+        summary.resources.push(GeneratedResource {
+            generator: "resource".to_string(),
+            name: "invoice".to_string(),
+        });
+        summary.hints.push(Hint::ManualInsert {
+            file: PathBuf::from("src/main.rs"),
+            line: "mod invoice;".to_string(),
+        });
+        let json = serde_json::to_value(&summary).unwrap();
+
+        let keys: Vec<&str> = json
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "dry_run",
+                "extras",
+                "files",
+                "hints",
+                "options",
+                "path",
+                "project",
+                "resources",
+                "skipped_extras",
+                "template"
+            ]
+        );
+        assert_eq!(json["project"], "demo");
+        assert_eq!(json["template"]["name"], "rest");
+        assert_eq!(json["extras"], serde_json::json!(["ci"]));
+        assert_eq!(json["skipped_extras"], serde_json::json!(["infra"]));
+        assert_eq!(json["options"]["docker"], true);
+        assert_eq!(
+            json["files"][0],
+            serde_json::json!({
+                "path": "Cargo.toml",
+                "outcome": "planned",
+                "size": 3,
+                "sha256": "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            })
+        );
+        assert_eq!(json["files"][1]["sha256"], EMPTY_SHA256);
+        assert_eq!(
+            json["resources"],
+            serde_json::json!([{ "generator": "resource", "name": "invoice" }])
+        );
+        assert_eq!(
+            json["hints"],
+            serde_json::json!([{ "kind": "manual_insert", "file": "src/main.rs", "line": "mod invoice;" }])
+        );
+
+        let json = serde_json::to_value(rest_summary(Some(Workspace { worker: true }))).unwrap();
+        assert!(json.get("workspace").is_some(), "{}", json);
+    }
+
+    #[test]
+    fn edited_files_keep_their_first_outcome_with_their_final_contents() {
+        let dir = std::env::temp_dir()
+            .join("create_woragis-tests")
+            .join(format!("summary-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("README.md.new"), "").unwrap();
+
+        let mut summary = rest_summary(None);
+        let created = Report {
+            entries: vec![
+                (PathBuf::from("main.rs"), Outcome::Created),
+                (
+                    PathBuf::from("README.md"),
+                    Outcome::NewCopy(PathBuf::from("README.md.new")),
+                ),
+                (PathBuf::from("gone.rs"), Outcome::Removed),
+            ],
+        };
+        summary.add_report(&dir, &created).unwrap();
+        fs::write(dir.join("main.rs"), "").unwrap();
+        let updated = Report {
+            entries: vec![(PathBuf::from("main.rs"), Outcome::Updated)],
+        };
+        summary.add_report(&dir, &updated).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let files: Vec<(&Path, &str, u64, &str)> = summary
+            .files
+            .iter()
+            .map(|file| {
+                (
+                    file.path.as_path(),
+                    file.outcome,
+                    file.size,
+                    file.sha256.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            files,
+            [
+                (Path::new("main.rs"), "created", 0, EMPTY_SHA256),
+                (Path::new("README.md.new"), "new_copy", 0, EMPTY_SHA256),
+            ]
+        );
+    }
+
+    #[test]
+    fn env_hints_come_from_the_planned_env_example() {
+        let mut summary = rest_summary(None);
+        summary.add_env_hints(&plan(&[(
+            ".env.example",
+            "# Database\nDATABASE_URL = postgres://localhost/demo\n\nSECRET_KEY=change-me\n",
+        )]));
+        let hints = serde_json::to_value(&summary.hints).unwrap();
+        assert_eq!(
+            hints,
+            serde_json::json!([
+                { "kind": "env", "name": "DATABASE_URL", "example": "postgres://localhost/demo" },
+                { "kind": "env", "name": "SECRET_KEY", "example": "change-me" },
+            ])
+        );
+    }
+}
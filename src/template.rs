@@ -0,0 +1,193 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::embedded;
+use crate::error::Error;
+use crate::manifest::{MANIFEST_FILE, Manifest};
+use crate::registry;
+use crate::remote::GitSource;
+
+/// A template selected by name, with its manifest already validated.
+#[derive(Debug, Clone)]
+pub struct Template {
+    /// Canonical name, i.e. the template's directory name.
+    pub name: String,
+    pub origin: Origin,
+    /// Every file of the template (manifest included), relative to its root.
+    pub files: Vec<TemplateFile>,
+    pub manifest: Manifest,
+}
+
+/// Where a template was loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// Compiled into the binary from `templates/`.
+    Builtin,
+    /// A directory in the user registry (see [`registry::templates_dir`]).
+    User(PathBuf),
+    /// A directory given with `--template-path`.
+    Local(PathBuf),
+    /// A `git+<url>#<ref>` template, checked out at `commit` into `dir`.
+    Git {
+        source: GitSource,
+        commit: String,
+        dir: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateFile {
+    pub path: PathBuf,
+    pub contents: Cow<'static, [u8]>,
+}
+
+impl Template {
+    /// Every available template, sorted by name.
+    ///
+    /// User templates shadow built-in ones with the same name, so a team can
+    /// replace `rest` with its own variant.
+    pub fn all() -> Result<Vec<Template>, Error> {
+        let mut templates: Vec<Template> = registry::templates()?
+            .into_iter()
+            .map(|(name, dir)| Template::from_dir(&name, Origin::User(dir.clone()), &dir))
+            .collect::<Result<_, _>>()?;
+        for name in embedded::template_names() {
+            if templates.iter().all(|t| t.name != name) {
+                templates.push(Template::builtin(&name)?);
+            }
+        }
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
+    /// Find a template by name or alias, or fetch it for a `git+` URL.
+    ///
+    /// Names are matched case-insensitively with `-` and `_` treated alike,
+    /// so `ai-rest`, `AI_REST` and `ai_rest` are the same template.
+    pub fn find(raw: &str) -> Result<Template, Error> {
+        if let Some(source) = GitSource::parse(raw) {
+            return Template::from_git(source);
+        }
+        let wanted = normalize(raw);
+        let templates = Template::all()?;
+        let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+        let available = names.join(", ");
+        templates
+            .into_iter()
+            .find(|t| {
+                normalize(&t.name) == wanted
+                    || t.manifest
+                        .template
+                        .aliases
+                        .iter()
+                        .any(|a| normalize(a) == wanted)
+            })
+            .ok_or_else(|| {
+                Error::UnknownTemplate(format!(
+                    "unknown template '{}' (available: {})",
+                    raw, available
+                ))
+            })
+    }
+
+    /// Load the template in a local directory (`--template-path`).
+    pub fn from_path(raw: &str) -> Result<Template, Error> {
+        let dir = PathBuf::from(raw);
+        if !dir.is_dir() {
+            return Err(Error::UnknownTemplate(format!(
+                "template directory '{}' does not exist",
+                raw
+            )));
+        }
+        let name = std::path::absolute(&dir)
+            .ok()
+            .and_then(|dir| dir.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| raw.to_string());
+        Template::from_dir(&name, Origin::Local(dir.clone()), &dir)
+    }
+
+    /// Check out a template from a git repository.
+    pub fn from_git(source: GitSource) -> Result<Template, Error> {
+        let checkout = source.checkout()?;
+        let name = source.name();
+        let dir = checkout.dir.clone();
+        let origin = Origin::Git {
+            source,
+            commit: checkout.commit,
+            dir: checkout.dir,
+        };
+        Template::from_dir(&name, origin, &dir)
+    }
+
+    /// Whether the template shows up in `list` and can be picked by name.
+    pub fn is_listed(&self) -> bool {
+        matches!(self.origin, Origin::Builtin | Origin::User(_))
+    }
+
+    /// The path errors and listings refer to the template by.
+    pub fn root(&self) -> PathBuf {
+        match &self.origin {
+            Origin::Builtin => PathBuf::from(&self.name),
+            Origin::User(dir) | Origin::Local(dir) | Origin::Git { dir, .. } => dir.clone(),
+        }
+    }
+
+    /// A file of the template by its relative path.
+    pub fn file(&self, path: impl AsRef<Path>) -> Option<&TemplateFile> {
+        self.files.iter().find(|file| file.path == path.as_ref())
+    }
+
+    fn builtin(name: &str) -> Result<Template, Error> {
+        let dir = embedded::template(name)
+            .ok_or_else(|| Error::UnknownTemplate(format!("unknown template '{}'", name)))?;
+        Template::new(name, Origin::Builtin, embedded::template_files(dir))
+    }
+
+    fn from_dir(name: &str, origin: Origin, dir: &Path) -> Result<Template, Error> {
+        Template::new(name, origin, registry::read_files(dir)?)
+    }
+
+    fn new(name: &str, origin: Origin, files: Vec<TemplateFile>) -> Result<Template, Error> {
+        let error = |message: String| Error::Manifest {
+            template: name.to_string(),
+            message,
+        };
+        let source = files
+            .iter()
+            .find(|file| file.path == Path::new(MANIFEST_FILE))
+            .ok_or_else(|| error(format!("'{}' is missing", MANIFEST_FILE)))?;
+        let source = std::str::from_utf8(&source.contents)
+            .map_err(|_| error(format!("'{}' is not valid UTF-8", MANIFEST_FILE)))?;
+        let manifest = Manifest::parse(source).map_err(error)?;
+        Ok(Template {
+            name: name.to_string(),
+            origin,
+            files,
+            manifest,
+        })
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Builtin => write!(f, "built-in"),
+            Origin::User(dir) => write!(f, "user: {}", dir.display()),
+            Origin::Local(dir) => write!(f, "local: {}", dir.display()),
+            Origin::Git { source, commit, .. } => write!(f, "{} at {}", source, commit),
+        }
+    }
+}
+
+/// Whether a file or directory named `name` is build output that a template
+/// directory may hold after being built in place (`Cargo.lock`, `target/`)
+/// rather than part of the template.
+pub fn is_build_artifact(name: &OsStr) -> bool {
+    name == "Cargo.lock" || name == "target"
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase().replace('-', "_")
+}
@@ -0,0 +1,249 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::generate::{Outcome, Report};
+use crate::lock::{self, Lock, LockedTemplate, Snapshot};
+use crate::manifest::Extra;
+use crate::plan::Plan;
+use crate::project::Project;
+use crate::render::{Context, Renderer};
+
+/// The result of an upgrade: per-file outcomes and the template before and
+/// after.
+pub struct Upgraded {
+    pub report: Report,
+    pub from: LockedTemplate,
+    pub to: LockedTemplate,
+}
+
+/// Upgrade the project to the current version of its template (for git
+/// templates, the one at `reference` if given).
+///
+/// The template is rendered again with the locked options and every file is
+/// merged three ways: the base snapshot from the last generation is the
+/// common ancestor, the project's file is ours and the new render theirs.
+/// Files the user never touched are simply replaced; overlapping changes are
+/// written with conflict markers. Files the template dropped are deleted
+/// unless the user changed them. Nothing is written with `dry_run`.
+pub fn upgrade(
+    project: &Project,
+    reference: Option<&str>,
+    dry_run: bool,
+) -> Result<Upgraded, Error> {
+    let root = &project.root;
+    let missing = |file: &str| {
+        Error::Project(format!(
+            "'{}' has no {}; only projects generated with a lock file can be upgraded",
+            root.display(),
+            file
+        ))
+    };
+    let lock = Lock::read(root)?.ok_or_else(|| missing(lock::LOCK_FILE))?;
+    let base = Snapshot::read(root)?.ok_or_else(|| missing(lock::BASE_FILE))?;
+
+    let template = lock.latest_template(reference)?;
+    let manifest = &template.manifest;
+    let variables = lock.variables(manifest, &[])?;
+    let extras: Vec<Extra> = lock
+        .project
+        .extras
+        .iter()
+        .copied()
+        .filter(|extra| manifest.supports(*extra))
+        .collect();
+    let (name, author) = (&lock.project.name, &lock.project.author);
+    let mut context = Context::new(name, author);
+    let workspace = lock.project.workspace;
+    context.set_options(&variables, &extras);
+    context.set_workspace(workspace.as_ref());
+    let plan = Plan::build(
+        &template,
+        &extras,
+        workspace.as_ref(),
+        &Renderer::new(&context),
+    )?;
+
+    let mut changes: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    let mut removed: Vec<PathBuf> = Vec::new();
+    let mut report = Report::default();
+    for file in &plan.files {
+        let existing = project.read(&file.path);
+        let outcome = match (base.get(&file.path), existing) {
+            (None, None) => {
+                changes.push((file.path.clone(), file.contents.clone()));
+                Outcome::Created
+            }
+            // The user deleted the file
+            (Some(_), None) => continue,
+            (Some(previous), _) if previous.as_bytes() == file.contents => continue,
+            (_, Some(existing)) if existing == file.contents => Outcome::Unchanged,
+            (Some(previous), Some(existing)) if previous.as_bytes() == existing => {
+                changes.push((file.path.clone(), file.contents.clone()));
+                Outcome::Updated
+            }
+            (Some(previous), Some(existing)) => {
+                match merge(previous.as_bytes(), &existing, &file.contents) {
+                    Merged::Clean(merged) => {
+                        changes.push((file.path.clone(), merged));
+                        Outcome::Updated
+                    }
+                    Merged::Conflict(conflicted) => {
+                        changes.push((file.path.clone(), conflicted));
+                        Outcome::Conflict
+                    }
+                    Merged::Binary => {
+                        let copy = new_copy(&file.path);
+                        changes.push((copy.clone(), file.contents.clone()));
+                        Outcome::NewCopy(copy)
+                    }
+                }
+            }
+            // New in the template, but the project has its own file there
+            (None, Some(_)) => {
+                let copy = new_copy(&file.path);
+                changes.push((copy.clone(), file.contents.clone()));
+                Outcome::NewCopy(copy)
+            }
+        };
+        report.entries.push((file.path.clone(), outcome));
+    }
+
+    for path in base.paths() {
+        if plan.files.iter().any(|file| file.path == path) {
+            continue;
+        }
+        let Some(existing) = project.read(&path) else {
+            continue;
+        };
+        let outcome = if base.get(&path).map(str::as_bytes) == Some(existing.as_slice()) {
+            removed.push(path.clone());
+            Outcome::Removed
+        } else {
+            Outcome::Skipped
+        };
+        report.entries.push((path, outcome));
+    }
+
+    let upgraded = Lock::new(&template, name, author, &variables, &extras, workspace);
+    if !dry_run {
+        project.write(changes)?;
+        for path in removed {
+            let path = root.join(path);
+            fs::remove_file(&path).map_err(|e| Error::io(&path, e))?;
+        }
+        upgraded.write(root)?;
+        Snapshot::of(&plan).write(root)?;
+    }
+    Ok(Upgraded {
+        report,
+        from: lock.template,
+        to: upgraded.template,
+    })
+}
+
+/// The result of [`merge`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum Merged {
+    Clean(Vec<u8>),
+    /// Merged, with `<<<<<<<` markers around the overlapping changes.
+    Conflict(Vec<u8>),
+    /// Any side is not UTF-8, so there is nothing to merge line by line.
+    Binary,
+}
+
+/// Merge the changes from `base` to `theirs` (the template's old and new
+/// render) into `ours` (the project's file), three ways.
+pub fn merge(base: &[u8], ours: &[u8], theirs: &[u8]) -> Merged {
+    let texts = (
+        std::str::from_utf8(base),
+        std::str::from_utf8(ours),
+        std::str::from_utf8(theirs),
+    );
+    let (Ok(base), Ok(ours), Ok(theirs)) = texts else {
+        return Merged::Binary;
+    };
+    match diffy::merge(base, ours, theirs) {
+        Ok(merged) => Merged::Clean(merged.into_bytes()),
+        Err(conflicted) => Merged::Conflict(conflicted.into_bytes()),
+    }
+}
+
+/// `<name>.new` next to `path`.
+pub fn new_copy(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".new");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "mod data;\nmod routes;\n\nfn main() {\n    serve();\n}\n";
+
+    fn text(merged: Merged) -> (bool, String) {
+        match merged {
+            Merged::Clean(merged) => (true, String::from_utf8(merged).unwrap()),
+            Merged::Conflict(merged) => (false, String::from_utf8(merged).unwrap()),
+            Merged::Binary => panic!("merged as binary"),
+        }
+    }
+
+    #[test]
+    fn untouched_files_take_the_new_version() {
+        let theirs = BASE.replace("mod data;\n", "mod cache;\nmod data;\n");
+        let merged = merge(BASE.as_bytes(), BASE.as_bytes(), theirs.as_bytes());
+        assert_eq!(text(merged), (true, theirs));
+    }
+
+    #[test]
+    fn separate_changes_are_both_kept() {
+        let ours = BASE.replace("    serve();\n", "    // Ours\n    serve();\n");
+        let theirs = BASE.replace("mod data;\n", "mod cache;\nmod data;\n");
+        let merged = merge(BASE.as_bytes(), ours.as_bytes(), theirs.as_bytes());
+        assert_eq!(
+            text(merged),
+            (
+                true,
+                "mod cache;\nmod data;\nmod routes;\n\nfn main() {\n    // Ours\n    serve();\n}\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn changed_lines_are_replaced_not_duplicated() {
+        let base = "use axum::middleware::{from_fn, Next};\n\nfn main() {}\n";
+        let theirs = "use axum::middleware::{from_fn, from_fn_with_state, Next};\n\nfn main() {}\n";
+        let ours = format!("{}\nfn helper() {{}}\n", base);
+        let (clean, merged) = text(merge(base.as_bytes(), ours.as_bytes(), theirs.as_bytes()));
+        assert!(clean);
+        assert_eq!(merged, format!("{}\nfn helper() {{}}\n", theirs));
+    }
+
+    #[test]
+    fn overlapping_changes_conflict() {
+        let ours = BASE.replace("    serve();\n", "    serve_forever();\n");
+        let theirs = BASE.replace("    serve();\n", "    serve_with(config);\n");
+        let (clean, merged) = text(merge(BASE.as_bytes(), ours.as_bytes(), theirs.as_bytes()));
+        assert!(!clean);
+        assert!(merged.contains("<<<<<<<"), "{}", merged);
+        assert!(merged.contains("serve_forever();"), "{}", merged);
+        assert!(merged.contains("serve_with(config);"), "{}", merged);
+        assert!(merged.contains(">>>>>>>"), "{}", merged);
+    }
+
+    #[test]
+    fn binary_files_are_not_merged() {
+        assert_eq!(merge(b"a\n", &[0xff, 0xfe], b"b\n"), Merged::Binary);
+    }
+
+    #[test]
+    fn new_copies_sit_next_to_the_file() {
+        assert_eq!(
+            new_copy(Path::new("src/main.rs")),
+            Path::new("src/main.rs.new")
+        );
+    }
+}
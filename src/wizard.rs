@@ -0,0 +1,343 @@
+use clap::ValueEnum;
+
+use crate::Cli;
+use crate::error::Error;
+use crate::license::License;
+use crate::manifest::{Extra, VarKind, VarValue};
+use crate::name;
+use crate::prompt::Prompter;
+use crate::template::Template;
+use crate::workspace::Workspace;
+
+/// The license choice that writes no license files.
+const NO_LICENSE: &str = "none";
+
+/// Walk the user through every choice, filling in `args` and returning the
+/// template picked.
+///
+/// Values already present in `args`, and `current`, are offered as defaults.
+/// Fails with [`Error::Aborted`] when the user declines the final summary.
+pub fn run(
+    prompter: &mut impl Prompter,
+    args: &mut Cli,
+    current: Template,
+) -> Result<Template, Error> {
+    let name = loop {
+        let name = prompter.input("Project name", args.name.as_deref())?;
+        match name::resolve(&name, args.path.as_deref()) {
+            Ok(_) => break name,
+            Err(e) => prompter.say(&format!("  {}", e))?,
+        }
+    };
+
+    // A --template-path or git template is not in the list, so keep it as is
+    let template = if !current.is_listed() {
+        current
+    } else {
+        let templates = Template::all()?;
+        let names: Vec<String> = templates.iter().map(|t| t.name.clone()).collect();
+        let picked = prompter.select("Template", &names, &current.name)?;
+        templates
+            .into_iter()
+            .find(|t| t.name == picked)
+            .expect("selected template is one of the choices")
+    };
+    let manifest = &template.manifest;
+    prompter.say(&format!("  {}", manifest.template.description))?;
+
+    let mut vars = Vec::new();
+    for variable in &manifest.variables {
+        let question = variable.prompt.as_deref().unwrap_or(&variable.name);
+        let preset = args
+            .vars
+            .iter()
+            .rev()
+            .find(|(key, _)| key == &variable.name);
+        let default = match preset {
+            Some((_, raw)) => Some(variable.parse(raw).map_err(Error::Variable)?),
+            None => variable.default.clone(),
+        };
+        let default = default.as_ref();
+        let answer = match variable.kind {
+            VarKind::Bool => {
+                let default = matches!(default, Some(VarValue::Bool(true)));
+                prompter.confirm(question, default)?.to_string()
+            }
+            VarKind::List => {
+                let defaults = match default {
+                    Some(VarValue::List(items)) => items.clone(),
+                    _ => Vec::new(),
+                };
+                prompter
+                    .multi_select(question, &variable.choices, &defaults)?
+                    .join(",")
+            }
+            VarKind::String if !variable.choices.is_empty() => {
+                let default = default
+                    .map(VarValue::to_string)
+                    .unwrap_or_else(|| variable.choices[0].clone());
+                prompter.select(question, &variable.choices, &default)?
+            }
+            VarKind::String => {
+                let default = default.map(VarValue::to_string);
+                prompter.input(question, default.as_deref())?
+            }
+        };
+        vars.push((variable.name.clone(), answer));
+    }
+
+    let with_ci = manifest.supports(Extra::Ci)
+        && prompter.confirm("Include GitHub Actions CI?", args.with_ci)?;
+    let with_infra = manifest.supports(Extra::Infra)
+        && prompter.confirm("Include Terraform infrastructure?", args.with_infra)?;
+    let workspace = Workspace::supports(&template)
+        && prompter.confirm(
+            "Generate a Cargo workspace (api and shared crates)?",
+            args.workspace,
+        )?;
+    let with_worker =
+        workspace && prompter.confirm("Include a background worker crate?", args.with_worker)?;
+
+    let mut licenses = vec![NO_LICENSE.to_string()];
+    licenses.extend(
+        License::value_variants()
+            .iter()
+            .filter_map(|license| license.to_possible_value())
+            .map(|value| value.get_name().to_string()),
+    );
+    let current = args
+        .license
+        .and_then(|license| license.to_possible_value())
+        .map_or(NO_LICENSE.to_string(), |value| value.get_name().to_string());
+    let license = prompter.select("License", &licenses, &current)?;
+
+    prompter.say("")?;
+    prompter.say("Summary")?;
+    let mut summary = vec![
+        ("project".to_string(), name.clone()),
+        ("template".to_string(), template.name.clone()),
+    ];
+    summary.extend(vars.iter().cloned());
+    summary.push(("license".to_string(), license.clone()));
+    summary.push(("ci".to_string(), yes_no(with_ci || with_infra).to_string()));
+    summary.push(("terraform".to_string(), yes_no(with_infra).to_string()));
+    if Workspace::supports(&template) {
+        summary.push(("workspace".to_string(), yes_no(workspace).to_string()));
+        summary.push(("worker".to_string(), yes_no(with_worker).to_string()));
+    }
+    for (key, value) in &summary {
+        prompter.say(&format!("  {:<14} {}", format!("{}:", key), value))?;
+    }
+    if !prompter.confirm("Create project?", true)? {
+        return Err(Error::Aborted);
+    }
+
+    args.name = Some(name);
+    args.vars = vars;
+    args.with_ci = with_ci;
+    args.with_infra = with_infra;
+    args.license = License::parse(&license).ok();
+    args.workspace = workspace;
+    args.with_worker = with_worker;
+    Ok(template)
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use clap::Parser;
+
+    use super::*;
+
+    /// Answers questions from a script: each `(needle, answer)` pair answers
+    /// the next question containing `needle`, in order. Other questions take
+    /// their default, or hit end of input with `eof`.
+    struct Script {
+        answers: Vec<(&'static str, &'static str)>,
+        eof: bool,
+        asked: Vec<String>,
+        said: Vec<String>,
+    }
+
+    impl Script {
+        fn new(answers: &[(&'static str, &'static str)]) -> Script {
+            Script {
+                answers: answers.to_vec(),
+                eof: false,
+                asked: Vec::new(),
+                said: Vec::new(),
+            }
+        }
+    }
+
+    impl Prompter for Script {
+        fn ask(&mut self, question: &str, default: Option<&str>) -> Result<String, Error> {
+            self.asked.push(question.to_string());
+            assert!(
+                self.asked.len() < 100,
+                "the wizard keeps asking: {:?}",
+                self.asked
+            );
+            let scripted = self
+                .answers
+                .iter()
+                .position(|(needle, _)| question.contains(needle));
+            let answer = match scripted {
+                Some(index) => self.answers.remove(index).1,
+                None if self.eof => {
+                    return Err(Error::Prompt(io::Error::from(io::ErrorKind::UnexpectedEof)));
+                }
+                None => "",
+            };
+            Ok(match (answer, default) {
+                ("", Some(default)) => default.to_string(),
+                _ => answer.to_string(),
+            })
+        }
+
+        fn say(&mut self, message: &str) -> Result<(), Error> {
+            self.said.push(message.to_string());
+            Ok(())
+        }
+    }
+
+    fn args() -> Cli {
+        Cli::try_parse_from(["create_woragis_api"]).expect("no arguments parse")
+    }
+
+    fn rest() -> Template {
+        Template::find(crate::DEFAULT_TEMPLATE).unwrap()
+    }
+
+    #[test]
+    fn empty_answers_take_the_defaults() {
+        let mut script = Script::new(&[("Project name", "demo")]);
+        let mut args = args();
+        let template = run(&mut script, &mut args, rest()).unwrap();
+
+        assert_eq!(args.name.as_deref(), Some("demo"));
+        assert_eq!(template.name, "rest");
+        let var = |key: &str| {
+            args.vars
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(var("framework"), Some("actix"));
+        assert_eq!(var("database"), Some("postgres"));
+        assert_eq!(var("features"), Some("auth,admin,cache,rate-limit"));
+        assert!(!args.with_ci && !args.with_infra);
+        assert!(!args.workspace && !args.with_worker);
+        assert_eq!(args.license, None);
+    }
+
+    #[test]
+    fn flags_are_offered_as_defaults() {
+        let mut script = Script::new(&[]);
+        let mut args = Cli::try_parse_from([
+            "create_woragis_api",
+            "demo",
+            "--var",
+            "database=sqlite",
+            "--with-ci",
+            "--license",
+            "MIT",
+        ])
+        .unwrap();
+        run(&mut script, &mut args, rest()).unwrap();
+
+        assert_eq!(args.name.as_deref(), Some("demo"));
+        assert!(
+            args.vars
+                .contains(&("database".to_string(), "sqlite".to_string()))
+        );
+        assert!(args.with_ci);
+        assert_eq!(args.license, Some(License::Mit));
+    }
+
+    #[test]
+    fn invalid_names_are_asked_again() {
+        let mut script = Script::new(&[
+            ("Project name", "1api"),
+            ("Project name", "fn"),
+            ("Project name", "std"),
+            ("Project name", "my api"),
+            ("Project name", "my-api"),
+        ]);
+        let mut args = args();
+        run(&mut script, &mut args, rest()).unwrap();
+
+        assert_eq!(args.name.as_deref(), Some("my-api"));
+        let asked = script
+            .asked
+            .iter()
+            .filter(|question| question.contains("Project name"))
+            .count();
+        assert_eq!(asked, 5);
+        let said = script.said.join("\n");
+        assert!(said.contains("cannot start with a digit"), "{}", said);
+        assert!(said.contains("is a Rust keyword"), "{}", said);
+        assert!(said.contains("is reserved by Cargo"), "{}", said);
+        assert!(said.contains("invalid character ' '"), "{}", said);
+    }
+
+    #[test]
+    fn invalid_choices_are_asked_again() {
+        let mut script = Script::new(&[
+            ("Project name", "demo"),
+            ("Web framework", "rocket"),
+            ("Web framework", "2"),
+            ("Features", "auth,graphql"),
+            ("Features", "none"),
+        ]);
+        let mut args = args();
+        run(&mut script, &mut args, rest()).unwrap();
+
+        assert!(
+            args.vars
+                .contains(&("framework".to_string(), "axum".to_string()))
+        );
+        assert!(args.vars.contains(&("features".to_string(), String::new())));
+        let said = script.said.join("\n");
+        assert!(
+            said.contains("'rocket' is not one of the choices"),
+            "{}",
+            said
+        );
+        assert!(
+            said.contains("'graphql' is not one of the choices"),
+            "{}",
+            said
+        );
+    }
+
+    #[test]
+    fn declining_the_summary_aborts() {
+        let mut script = Script::new(&[("Project name", "demo"), ("Create project?", "n")]);
+        let mut args = args();
+        let result = run(&mut script, &mut args, rest());
+
+        assert!(matches!(result, Err(Error::Aborted)), "{:?}", result.err());
+        assert_eq!(args.name, None);
+    }
+
+    #[test]
+    fn end_of_input_stops_the_wizard() {
+        let mut script = Script::new(&[("Project name", "demo")]);
+        script.eof = true;
+        let mut args = args();
+        let result = run(&mut script, &mut args, rest());
+
+        assert!(
+            matches!(result, Err(Error::Prompt(_))),
+            "{:?}",
+            result.err()
+        );
+        assert_eq!(args.name, None);
+    }
+}
@@ -0,0 +1,448 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, InlineTable, Item, Table};
+
+use crate::error::Error;
+use crate::manifest::Manifest;
+use crate::plan::{self, PlannedFile};
+use crate::render::Renderer;
+use crate::template::{Template, TemplateFile};
+
+/// Directory of a template holding what only workspaces get: the shared
+/// crate in `shared/` and the optional worker in `worker/`.
+pub const WORKSPACE_DIR: &str = "workspace";
+
+/// Directory of a workspace holding its member crates.
+pub const CRATES_DIR: &str = "crates";
+
+/// Member crate built from the template itself.
+pub const API_CRATE: &str = "api";
+
+/// Member crate holding what the services share.
+pub const SHARED_CRATE: &str = "shared";
+
+/// Optional member crate for background jobs.
+pub const WORKER_CRATE: &str = "worker";
+
+/// What belongs to a member crate rather than to the workspace root; the
+/// rest (README, `.env.example`, Dockerfile, extras) stays at the root.
+const CRATE_PATHS: &[&str] = &[
+    "Cargo.toml",
+    "build.rs",
+    "src",
+    "migrations",
+    "proto",
+    "tests",
+    "benches",
+    "examples",
+];
+
+/// A project generated with `--workspace`, as recorded in its lock file.
+///
+/// The template becomes the `api` crate, the files its manifest lists under
+/// `shared` move into the `shared` crate, and both get the dependencies of
+/// the template's `Cargo.toml` from `[workspace.dependencies]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workspace {
+    /// Whether there is a `worker` crate.
+    #[serde(default)]
+    pub worker: bool,
+}
+
+impl Workspace {
+    /// Whether `template` can be generated as a workspace, i.e. has a
+    /// `workspace/shared` crate.
+    pub fn supports(template: &Template) -> bool {
+        let shared = Path::new(WORKSPACE_DIR).join(SHARED_CRATE);
+        template
+            .files
+            .iter()
+            .any(|file| file.path.starts_with(&shared))
+    }
+
+    /// The members, relative to the workspace root.
+    pub fn members(&self) -> Vec<PathBuf> {
+        let mut members = vec![API_CRATE, SHARED_CRATE];
+        if self.worker {
+            members.push(WORKER_CRATE);
+        }
+        members
+            .into_iter()
+            .map(|member| Path::new(CRATES_DIR).join(member))
+            .collect()
+    }
+
+    /// Package names of the binary members.
+    pub fn services(&self) -> Vec<String> {
+        let mut services = vec![API_CRATE.to_string()];
+        if self.worker {
+            services.push(WORKER_CRATE.to_string());
+        }
+        services
+    }
+
+    /// Package names of the services that run background jobs rather than
+    /// answer requests.
+    pub fn workers(&self) -> Vec<String> {
+        if self.worker {
+            vec![WORKER_CRATE.to_string()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Where the file the template (or one of its generators) puts at `path`
+    /// lives in the workspace.
+    pub fn place(manifest: &Manifest, path: &Path) -> PathBuf {
+        let crates = Path::new(CRATES_DIR);
+        if manifest
+            .template
+            .shared
+            .iter()
+            .any(|shared| path.starts_with(shared))
+        {
+            crates.join(SHARED_CRATE).join(path)
+        } else if CRATE_PATHS.iter().any(|prefix| path.starts_with(prefix)) {
+            crates.join(API_CRATE).join(path)
+        } else {
+            path.to_path_buf()
+        }
+    }
+
+    /// Lay the rendered single-crate `files` of `template` out as a
+    /// workspace: move them into their crates, render the shared crate and
+    /// the worker, and write the workspace `Cargo.toml`.
+    pub fn apply(
+        &self,
+        template: &Template,
+        renderer: &Renderer,
+        files: &mut Vec<PlannedFile>,
+    ) -> Result<(), Error> {
+        let manifest = &template.manifest;
+        for file in files.iter_mut() {
+            file.path = Workspace::place(manifest, &file.path);
+        }
+
+        let mut crates = vec![SHARED_CRATE];
+        if self.worker {
+            crates.push(WORKER_CRATE);
+        }
+        for member in crates {
+            let source = Path::new(WORKSPACE_DIR).join(member);
+            let sources: Vec<_> = template
+                .files
+                .iter()
+                .filter(|file| file.path.starts_with(&source))
+                .map(|file| TemplateFile {
+                    path: file
+                        .path
+                        .strip_prefix(&source)
+                        .expect("filtered by prefix")
+                        .to_path_buf(),
+                    contents: file.contents.clone(),
+                })
+                .collect();
+            let root = template.root().join(&source);
+            let target = Path::new(CRATES_DIR).join(member);
+            for (path, contents) in plan::render_files(&sources, &root, renderer, |_| Ok(true))? {
+                files.push(PlannedFile {
+                    path: target.join(path),
+                    contents,
+                });
+            }
+        }
+
+        // The template's dependencies move up into the workspace
+        let api_manifest = Path::new(CRATES_DIR).join(API_CRATE).join("Cargo.toml");
+        let mut dependencies = Table::new();
+        let mut shared = InlineTable::new();
+        shared.insert(
+            "path",
+            format!("{}/{}", CRATES_DIR, SHARED_CRATE).as_str().into(),
+        );
+        dependencies.insert(SHARED_CRATE, toml_edit::value(shared));
+        if let Some(file) = files.iter_mut().find(|file| file.path == api_manifest) {
+            let invalid = |message: String| Error::Render {
+                path: template.root().join("Cargo.toml"),
+                message,
+            };
+            let text = String::from_utf8(file.contents.clone())
+                .map_err(|_| invalid("not valid UTF-8".to_string()))?;
+            let mut cargo: DocumentMut = text.parse().map_err(|e| invalid(format!("{}", e)))?;
+            cargo["package"]["name"] = toml_edit::value(API_CRATE);
+            inherit_dependencies(&mut cargo, &mut dependencies);
+            if let Some(table) = cargo
+                .get_mut("dependencies")
+                .and_then(Item::as_table_like_mut)
+            {
+                table.insert(SHARED_CRATE, inherited());
+            }
+            file.contents = cargo.to_string().into_bytes();
+        }
+
+        let members: Vec<String> = self
+            .members()
+            .iter()
+            .map(|member| member.display().to_string())
+            .collect();
+        let mut workspace = Table::new();
+        workspace.insert("resolver", toml_edit::value("3"));
+        workspace.insert(
+            "members",
+            toml_edit::value(members.iter().collect::<toml_edit::Array>()),
+        );
+        workspace.insert("dependencies", Item::Table(dependencies));
+        let mut cargo = DocumentMut::new();
+        cargo.insert("workspace", Item::Table(workspace));
+        files.push(PlannedFile {
+            path: PathBuf::from("Cargo.toml"),
+            contents: cargo.to_string().into_bytes(),
+        });
+        Ok(())
+    }
+}
+
+/// A dependency a new member asked for in another version than the one in
+/// `[workspace.dependencies]`, which it now inherits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unified {
+    pub name: String,
+    pub wanted: String,
+    pub kept: String,
+}
+
+/// Make the dependencies of a member's `cargo` inherit from `dependencies`,
+/// the workspace's `[workspace.dependencies]`.
+///
+/// Dependencies the workspace lacks move into it as declared. The others
+/// keep the workspace's version and only add the features the member needs
+/// on top; those whose version differs are returned. `optional` stays with
+/// the member, as Cargo requires.
+pub fn inherit_dependencies(cargo: &mut DocumentMut, dependencies: &mut Table) -> Vec<Unified> {
+    let mut unified = Vec::new();
+    for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        let Some(table) = cargo.get_mut(section).and_then(Item::as_table_like_mut) else {
+            continue;
+        };
+        for (name, spec) in table.iter_mut() {
+            if spec.get("workspace").and_then(Item::as_bool) == Some(true) {
+                continue;
+            }
+            let mut member = InlineTable::new();
+            member.insert("workspace", true.into());
+            let mut declared = spec.clone();
+            if let Some(optional) = declared
+                .as_table_like_mut()
+                .and_then(|table| table.remove("optional"))
+                .and_then(|optional| optional.into_value().ok())
+            {
+                member.insert("optional", optional);
+                // Without it the spacing before the closing brace is off
+                if let Some(table) = declared.as_inline_table_mut() {
+                    table.fmt();
+                }
+            }
+
+            match dependencies.get(&name) {
+                None => {
+                    dependencies.insert(&name, declared);
+                }
+                Some(existing) => {
+                    if let (Some(wanted), Some(kept)) = (version(&declared), version(existing))
+                        && wanted != kept
+                    {
+                        unified.push(Unified {
+                            name: name.to_string(),
+                            wanted,
+                            kept,
+                        });
+                    }
+                    let present = features(existing);
+                    let missing: toml_edit::Array = features(&declared)
+                        .into_iter()
+                        .filter(|feature| !present.contains(feature))
+                        .collect();
+                    if !missing.is_empty() {
+                        member.insert("features", missing.into());
+                    }
+                }
+            }
+            *spec = toml_edit::value(member);
+        }
+    }
+    unified
+}
+
+/// The version requirement of a dependency declaration.
+fn version(spec: &Item) -> Option<String> {
+    spec.as_str()
+        .or_else(|| spec.get("version").and_then(Item::as_str))
+        .map(str::to_string)
+}
+
+/// The features a dependency declaration enables.
+fn features(spec: &Item) -> Vec<String> {
+    spec.get("features")
+        .and_then(Item::as_array)
+        .map(|features| {
+            features
+                .iter()
+                .filter_map(|feature| feature.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `{ workspace = true }`
+fn inherited() -> Item {
+    let mut table = InlineTable::new();
+    table.insert("workspace", true.into());
+    toml_edit::value(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::Plan;
+    use crate::render::Context;
+
+    fn rest() -> Template {
+        Template::find(crate::DEFAULT_TEMPLATE).unwrap()
+    }
+
+    #[test]
+    fn the_worker_is_a_member_and_a_service() {
+        let workspace = Workspace { worker: false };
+        assert_eq!(
+            workspace.members(),
+            [Path::new("crates/api"), Path::new("crates/shared")]
+        );
+        assert_eq!(workspace.services(), ["api"]);
+        assert!(workspace.workers().is_empty());
+
+        let workspace = Workspace { worker: true };
+        assert_eq!(
+            workspace.members(),
+            [
+                Path::new("crates/api"),
+                Path::new("crates/shared"),
+                Path::new("crates/worker")
+            ]
+        );
+        assert_eq!(workspace.services(), ["api", "worker"]);
+        assert_eq!(workspace.workers(), ["worker"]);
+    }
+
+    #[test]
+    fn files_go_to_their_crate_or_stay_at_the_root() {
+        let manifest = &rest().manifest;
+        for (path, placed) in [
+            ("src/main.rs", "crates/api/src/main.rs"),
+            ("Cargo.toml", "crates/api/Cargo.toml"),
+            ("src/models/user.rs", "crates/shared/src/models/user.rs"),
+            (
+                "migrations/0001_create_users.sql",
+                "crates/shared/migrations/0001_create_users.sql",
+            ),
+            ("README.md", "README.md"),
+            ("Dockerfile", "Dockerfile"),
+        ] {
+            assert_eq!(
+                Workspace::place(manifest, Path::new(path)),
+                Path::new(placed),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn the_root_manifest_lists_the_members_and_their_dependencies() {
+        let template = rest();
+        let workspace = Workspace { worker: true };
+        let mut context = Context::new("demo", "Test");
+        context.set_options(&template.manifest.resolve_variables(&[]).unwrap(), &[]);
+        context.set_workspace(Some(&workspace));
+        let plan = Plan::build(&template, &[], Some(&workspace), &Renderer::new(&context)).unwrap();
+        let manifest = |path: &str| -> DocumentMut {
+            let file = plan
+                .files
+                .iter()
+                .find(|file| file.path == Path::new(path))
+                .unwrap_or_else(|| panic!("no {}", path));
+            String::from_utf8(file.contents.clone())
+                .unwrap()
+                .parse()
+                .unwrap()
+        };
+
+        let root = manifest("Cargo.toml");
+        assert!(root.get("package").is_none());
+        let members: Vec<&str> = root["workspace"]["members"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|member| member.as_str())
+            .collect();
+        assert_eq!(members, ["crates/api", "crates/shared", "crates/worker"]);
+        let dependencies = &root["workspace"]["dependencies"];
+        assert_eq!(
+            dependencies["shared"]["path"].as_str(),
+            Some("crates/shared")
+        );
+        assert!(dependencies.get("tokio").is_some());
+
+        let api = manifest("crates/api/Cargo.toml");
+        assert_eq!(api["package"]["name"].as_str(), Some("api"));
+        for (name, spec) in api["dependencies"].as_table().unwrap() {
+            assert_eq!(spec["workspace"].as_bool(), Some(true), "{}", name);
+        }
+        assert!(api["dependencies"].get("shared").is_some());
+        for path in ["crates/shared/Cargo.toml", "crates/worker/Cargo.toml"] {
+            assert!(
+                plan.files.iter().any(|file| file.path == Path::new(path)),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn members_inherit_the_workspace_dependencies() {
+        let mut dependencies: Table =
+            "serde = { version = \"1.0.200\", features = [\"derive\"] }\n"
+                .parse::<DocumentMut>()
+                .unwrap()
+                .as_table()
+                .clone();
+        let mut cargo: DocumentMut = "[dependencies]\n\
+            serde = { version = \"1.0.100\", features = [\"derive\", \"rc\"] }\n\
+            redis = { version = \"0.27\", optional = true }\n\
+            log = { workspace = true }\n"
+            .parse()
+            .unwrap();
+
+        let unified = inherit_dependencies(&mut cargo, &mut dependencies);
+
+        assert_eq!(
+            unified,
+            [Unified {
+                name: "serde".to_string(),
+                wanted: "1.0.100".to_string(),
+                kept: "1.0.200".to_string(),
+            }]
+        );
+        assert_eq!(
+            cargo["dependencies"].to_string(),
+            "serde = { workspace = true, features = [\"rc\"] }\n\
+             redis = { workspace = true, optional = true }\n\
+             log = { workspace = true }\n"
+        );
+        // New dependencies move up as declared, without `optional`
+        assert_eq!(
+            dependencies["redis"].to_string().trim(),
+            "{ version = \"0.27\" }"
+        );
+    }
+}
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS {{ table }} (
+    id {{ {"sqlite": "BLOB", "mysql": "BINARY(16)"}[database] | default("UUID") }} PRIMARY KEY{{ "," if fields }}
+{% for field in fields %}
+    {{ field.name }} {{ field.sql }}{% if not field.optional %} NOT NULL{% endif %}{% if not loop.last %},{% endif %}
+
+{% endfor %}
+);
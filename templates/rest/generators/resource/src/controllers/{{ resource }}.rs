@@ -0,0 +1,81 @@
+{% set model = resource | pascal_case %}
+use uuid::Uuid;
+
+use crate::{
+    data::database::Pool,
+    models::{
+        response::{ApiError, ApiResponse},
+        {{ resource }}::{ {{- model }}, {{ model }}Input},
+    },
+    web::{Json, Path, Response, State, StatusCode},
+};
+
+/// **Create {{ model }}**
+pub async fn create_{{ resource }}(
+    pool: State<Pool>,
+    payload: Json<{{ model }}Input>,
+) -> Result<Response, ApiError> {
+    let {{ resource }} = {{ model }}::from_input(Uuid::new_v4(), payload.0);
+    {{ resource }}.insert(&pool).await?;
+
+    Ok(ApiResponse::success(
+        {{ resource }},
+        "{{ model }} created successfully",
+        StatusCode::CREATED,
+    ))
+}
+
+/// **Read {{ model }}**
+pub async fn get_{{ resource }}(pool: State<Pool>, id: Path<Uuid>) -> Result<Response, ApiError> {
+    let {{ resource }} = {{ model }}::get(&pool, *id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("{{ model }} {}", *id)))?;
+
+    Ok(ApiResponse::success(
+        {{ resource }},
+        "{{ model }} retrieved successfully",
+        StatusCode::OK,
+    ))
+}
+
+/// **Read {{ table | pascal_case }}**
+pub async fn get_{{ table }}(pool: State<Pool>) -> Result<Response, ApiError> {
+    let {{ table }} = {{ model }}::all(&pool).await?;
+
+    Ok(ApiResponse::success(
+        {{ table }},
+        "{{ table | pascal_case }} retrieved successfully",
+        StatusCode::OK,
+    ))
+}
+
+/// **Update {{ model }}**
+pub async fn update_{{ resource }}(
+    pool: State<Pool>,
+    id: Path<Uuid>,
+    payload: Json<{{ model }}Input>,
+) -> Result<Response, ApiError> {
+    if {{ model }}::get(&pool, *id).await?.is_none() {
+        return Err(ApiError::NotFound(format!("{{ model }} {}", *id)));
+    }
+    let {{ resource }} = {{ model }}::from_input(*id, payload.0);
+    {{ resource }}.update(&pool).await?;
+
+    Ok(ApiResponse::success(
+        {{ resource }},
+        "{{ model }} updated successfully",
+        StatusCode::OK,
+    ))
+}
+
+/// **Delete {{ model }}**
+pub async fn delete_{{ resource }}(pool: State<Pool>, id: Path<Uuid>) -> Result<Response, ApiError> {
+    match {{ model }}::delete(&pool, *id).await? {
+        false => Err(ApiError::NotFound(format!("{{ model }} {}", *id))),
+        true => Ok(ApiResponse::success(
+            id.to_string(),
+            "{{ model }} deleted successfully",
+            StatusCode::OK,
+        )),
+    }
+}
@@ -0,0 +1,129 @@
+{% set model = resource | pascal_case %}
+use serde::{Deserialize, Serialize};
+{% if database != 'none' %}
+use sqlx::{FromRow, QueryBuilder};
+{% endif %}
+use uuid::Uuid;
+
+{% if database == 'none' %}
+use crate::data::database::{{ '{' }}{{ table | upper }}_TABLE as TABLE, Pool};
+{% else %}
+use crate::data::database::{{ '{' }}{{ table | upper }}_TABLE as TABLE, Db, Pool};
+{% endif %}
+use crate::models::response::ApiError;
+
+{% if database == 'none' %}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+{% else %}
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+{% endif %}
+pub struct {{ model }} {
+    pub id: Uuid,
+{% for field in fields %}
+    pub {{ field.name }}: {{ field.rust }},
+{% endfor %}
+}
+
+/// Body of create and update requests.
+#[derive(Debug, Deserialize)]
+pub struct {{ model }}Input {
+{% for field in fields %}
+    pub {{ field.name }}: {{ field.rust }},
+{% endfor %}
+}
+
+impl {{ model }} {
+    pub fn from_input(id: Uuid, input: {{ model }}Input) -> Self {
+        {{ model }} {
+            id,
+{% for field in fields %}
+            {{ field.name }}: input.{{ field.name }},
+{% endfor %}
+        }
+    }
+{% if database == 'none' %}
+
+    pub async fn all(pool: &Pool) -> Result<Vec<{{ model }}>, ApiError> {
+        Ok(pool.all(TABLE))
+    }
+
+    pub async fn get(pool: &Pool, id: Uuid) -> Result<Option<{{ model }}>, ApiError> {
+        Ok(pool.get(TABLE, id))
+    }
+
+    pub async fn insert(&self, pool: &Pool) -> Result<(), ApiError> {
+        pool.put(TABLE, self.id, self.clone());
+        Ok(())
+    }
+
+    pub async fn update(&self, pool: &Pool) -> Result<(), ApiError> {
+        pool.put(TABLE, self.id, self.clone());
+        Ok(())
+    }
+
+    /// Returns whether the row existed.
+    pub async fn delete(pool: &Pool, id: Uuid) -> Result<bool, ApiError> {
+        Ok(pool.remove(TABLE, id))
+    }
+{% else %}
+
+    pub async fn all(pool: &Pool) -> Result<Vec<{{ model }}>, ApiError> {
+        let mut query = QueryBuilder::<Db>::new(format!("SELECT * FROM {}", TABLE));
+        query
+            .build_query_as()
+            .fetch_all(pool)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    pub async fn get(pool: &Pool, id: Uuid) -> Result<Option<{{ model }}>, ApiError> {
+        let mut query = QueryBuilder::<Db>::new(format!("SELECT * FROM {} WHERE id = ", TABLE));
+        query.push_bind(id);
+        query
+            .build_query_as()
+            .fetch_optional(pool)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    pub async fn insert(&self, pool: &Pool) -> Result<(), ApiError> {
+        let mut query = QueryBuilder::<Db>::new(format!(
+            "INSERT INTO {} (id{% for field in fields %}, {{ field.name }}{% endfor %}) VALUES (",
+            TABLE
+        ));
+        query
+            .separated(", ")
+            .push_bind(self.id){{ ';' if not fields }}
+{% for field in fields %}
+            .push_bind({{ '&' if field.type in ['string', 'json'] }}self.{{ field.name }}){{ ';' if loop.last }}
+{% endfor %}
+        query.push(")");
+        query.build().execute(pool).await.map_err(ApiError::from)?;
+        Ok(())
+    }
+
+    pub async fn update(&self, pool: &Pool) -> Result<(), ApiError> {
+        let mut query = QueryBuilder::<Db>::new(format!("UPDATE {} SET ", TABLE));
+        let mut fields = query.separated(", ");
+{% if not fields %}
+        fields.push("id = ").push_bind_unseparated(self.id);
+{% endif %}
+{% for field in fields %}
+        fields
+            .push("{{ field.name }} = ")
+            .push_bind_unseparated({{ '&' if field.type in ['string', 'json'] }}self.{{ field.name }});
+{% endfor %}
+        query.push(" WHERE id = ").push_bind(self.id);
+        query.build().execute(pool).await.map_err(ApiError::from)?;
+        Ok(())
+    }
+
+    /// Returns whether the row existed.
+    pub async fn delete(pool: &Pool, id: Uuid) -> Result<bool, ApiError> {
+        let mut query = QueryBuilder::<Db>::new(format!("DELETE FROM {} WHERE id = ", TABLE));
+        query.push_bind(id);
+        let result = query.build().execute(pool).await.map_err(ApiError::from)?;
+        Ok(result.rows_affected() > 0)
+    }
+{% endif %}
+}
@@ -0,0 +1,37 @@
+{% if framework == 'axum' %}
+use axum::{routing::get, Router};
+
+use crate::{
+    controllers::{{ resource }}::{
+        create_{{ resource }}, delete_{{ resource }}, get_{{ resource }}, get_{{ table }}, update_{{ resource }},
+    },
+    web::AppState,
+};
+
+pub fn {{ resource }}_routes() -> Router<AppState> {
+    Router::new()
+        .route("/{{ table | kebab_case }}/", get(get_{{ table }}).post(create_{{ resource }}))
+        .route(
+            "/{{ table | kebab_case }}/{id}",
+            get(get_{{ resource }}).put(update_{{ resource }}).delete(delete_{{ resource }}),
+        )
+}
+{% else %}
+use actix_web::{
+    web::{delete, get, post, put, scope},
+    Scope,
+};
+
+use crate::controllers::{{ resource }}::{
+    create_{{ resource }}, delete_{{ resource }}, get_{{ resource }}, get_{{ table }}, update_{{ resource }},
+};
+
+pub fn {{ resource }}_routes() -> Scope {
+    scope("/{{ table | kebab_case }}")
+        .route("/", get().to(get_{{ table }}))
+        .route("/", post().to(create_{{ resource }}))
+        .route("/{id}", get().to(get_{{ resource }}))
+        .route("/{id}", put().to(update_{{ resource }}))
+        .route("/{id}", delete().to(delete_{{ resource }}))
+}
+{% endif %}
@@ -0,0 +1,10 @@
+{% set uuid = {"postgres": "UUID", "sqlite": "BLOB", "mysql": "BINARY(16)"}[database] %}
+CREATE TABLE IF NOT EXISTS users (
+    id {{ uuid }} PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    email VARCHAR(255) NOT NULL UNIQUE,
+    password TEXT NOT NULL,
+    role VARCHAR(5) NOT NULL DEFAULT 'user' CHECK (role IN ('admin', 'user')){% if 'profile-picture' in features %},
+    profile_picture TEXT{% endif %}
+
+);
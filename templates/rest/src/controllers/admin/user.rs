@@ -1,61 +1,29 @@
-use core::panic;
-use std::sync::Arc;
+use uuid::Uuid;
 
 use crate::{
+    data::database::Pool,
     models::{
         response::{ApiError, ApiResponse, AuthError},
         user::{CreateUser, UpdateUser, User},
     },
-    utils::{
-        bcrypt::hash_password,
-        encryption::sha_encrypt_string,
-        jwt::{extract_token, validate_jwt},
-    },
-};
-use actix_web::{
-    http::StatusCode,
-    web::{self, Data, Json, Path},
-    HttpRequest, HttpResponse,
+    utils::{bcrypt::hash_password, jwt::Claims},
+    web::{Json, Path, Response, State, StatusCode},
 };
-use once_cell::sync::Lazy;
-use tokio::sync::Mutex;
-use tokio_postgres::Client;
-use uuid::Uuid;
-
-static TABLE: &str = "users";
-static FIELDS: &str = "name, email, password";
-static UPDATE_FIELDS: &str = "name = $1, email = $2, password = $3";
-static FIELDS_INPUT: &str = "$1, $2, $3";
-
-static ADMIN_ROLE: Lazy<String> =
-    Lazy::new(|| sha_encrypt_string("admin".to_string()).expect("msg"));
 
+/// **Create User**
 pub async fn create_user(
-    client: Data<Arc<Mutex<Client>>>,
-    request: HttpRequest,
-    payload: web::Json<CreateUser>,
-) -> Result<HttpResponse, ApiError> {
-    let token = extract_token(&request.headers()).map_err(ApiError::from)?;
-    let claims = validate_jwt(&token).map_err(ApiError::from)?;
-    let role = claims.role;
-    match role == ADMIN_ROLE.as_ref() {
-        true => (),
-        false => return Err(ApiError::Auth(AuthError::AdminsOnly)),
+    pool: State<Pool>,
+    claims: Claims,
+    payload: Json<CreateUser>,
+) -> Result<Response, ApiError> {
+    claims.require_admin()?;
+    if User::find_by_email(&pool, &payload.email).await?.is_some() {
+        return Err(ApiError::Auth(AuthError::EmailTaken));
     }
 
-    let hashed_password = hash_password(&payload.password).map_err(ApiError::from)?;
-
-    let client = client.lock().await;
-    let stmt = format!(
-        "INSERT INTO {} ({}) VALUES ({}) RETURNING *",
-        TABLE, FIELDS, FIELDS_INPUT
-    );
-    let row = client
-        .query_one(&stmt, &[&payload.name, &payload.email, &hashed_password])
-        .await
-        .map_err(ApiError::from)?;
-
-    let user = User::from_row(&row);
+    let hashed_password = hash_password(&payload.password)?;
+    let user = User::new(payload.name.clone(), payload.email.clone(), hashed_password);
+    user.insert(&pool).await?;
 
     Ok(ApiResponse::success(
         user,
@@ -66,26 +34,15 @@ pub async fn create_user(
 
 /// **Read User**
 pub async fn get_user(
-    client: Data<Arc<Mutex<Client>>>,
-    request: HttpRequest,
+    pool: State<Pool>,
+    claims: Claims,
     user_id: Path<Uuid>,
-) -> Result<HttpResponse, ApiError> {
-    let token = extract_token(&request.headers()).map_err(ApiError::from)?;
-    let claims = validate_jwt(&token).map_err(ApiError::from)?;
-    let role = claims.role;
-    match role == ADMIN_ROLE.as_ref() {
-        true => (),
-        false => return Err(ApiError::Auth(AuthError::AdminsOnly)),
-    }
+) -> Result<Response, ApiError> {
+    claims.require_admin()?;
 
-    let client = client.lock().await;
-    let stmt = format!("SELECT * FROM {} WHERE id = $1", TABLE);
-    let row = client
-        .query_one(&stmt, &[&*user_id])
-        .await
-        .map_err(ApiError::from)?;
-
-    let user = User::from_row(&row);
+    let user = User::get(&pool, *user_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("User {}", *user_id)))?;
 
     Ok(ApiResponse::success(
         user,
@@ -95,25 +52,10 @@ pub async fn get_user(
 }
 
 /// **Read Users**
-pub async fn get_users(
-    client: web::Data<Arc<Mutex<Client>>>,
-    request: HttpRequest,
-) -> Result<HttpResponse, ApiError> {
-    let token = extract_token(&request.headers()).map_err(ApiError::from)?;
-    let claims = validate_jwt(&token).map_err(ApiError::from)?;
-    let role = claims.role;
-    println!("user hashed role: {}", role);
-    println!("admin hashed role: {}", ADMIN_ROLE.to_string());
-    match role == ADMIN_ROLE.as_ref() {
-        true => (),
-        false => return Err(ApiError::Auth(AuthError::AdminsOnly)),
-    }
+pub async fn get_users(pool: State<Pool>, claims: Claims) -> Result<Response, ApiError> {
+    claims.require_admin()?;
 
-    let client = client.lock().await;
-    let stmt = format!("SELECT * FROM {}", TABLE);
-    let rows = client.query(&stmt, &[]).await.map_err(ApiError::from)?;
-
-    let users: Vec<User> = rows.iter().map(|row| User::from_row(row)).collect();
+    let users = User::all(&pool).await?;
 
     Ok(ApiResponse::success(
         users,
@@ -124,70 +66,42 @@ pub async fn get_users(
 
 /// **Update User**
 pub async fn update_user(
-    client: Data<Arc<Mutex<Client>>>,
-    request: HttpRequest,
+    pool: State<Pool>,
+    claims: Claims,
     user_id: Path<Uuid>,
     payload: Json<UpdateUser>,
-) -> Result<HttpResponse, ApiError> {
-    let token = extract_token(&request.headers()).map_err(ApiError::from)?;
-    let claims = validate_jwt(&token).map_err(ApiError::from)?;
-    let role = claims.role;
-    match role == ADMIN_ROLE.as_ref() {
-        true => (),
-        false => return Err(ApiError::Auth(AuthError::AdminsOnly)),
-    }
-
-    let hashed_password = hash_password(&payload.password).map_err(ApiError::from)?;
+) -> Result<Response, ApiError> {
+    claims.require_admin()?;
 
-    let client = client.lock().await;
-    let stmt = format!("UPDATE {} SET {} WHERE id = $4", TABLE, UPDATE_FIELDS);
-    let result = client
-        .execute(
-            &stmt,
-            &[&payload.name, &payload.email, &hashed_password, &*user_id],
-        )
-        .await
-        .map_err(ApiError::from);
+    let mut user = User::get(&pool, *user_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("User {}", *user_id)))?;
+    user.name = payload.name.clone();
+    user.email = payload.email.clone();
+    user.password = hash_password(&payload.password)?;
+    user.update(&pool).await?;
 
-    match result {
-        Ok(1) => Ok(ApiResponse::success(
-            *user_id,
-            "User updated successfully",
-            StatusCode::OK,
-        )),
-        // update response
-        _ => panic!("Error in update user"),
-    }
+    Ok(ApiResponse::success(
+        *user_id,
+        "User updated successfully",
+        StatusCode::OK,
+    ))
 }
 
 /// **Delete User**
 pub async fn delete_user(
-    client: Data<Arc<Mutex<Client>>>,
-    request: HttpRequest,
+    pool: State<Pool>,
+    claims: Claims,
     user_id: Path<Uuid>,
-) -> Result<HttpResponse, ApiError> {
-    let token = extract_token(&request.headers()).map_err(ApiError::from)?;
-    let claims = validate_jwt(&token).map_err(ApiError::from)?;
-    let role = claims.role;
-    match role == ADMIN_ROLE.as_ref() {
-        true => (),
-        false => return Err(ApiError::Auth(AuthError::AdminsOnly)),
-    }
-
-    let client = client.lock().await;
-    let stmt = format!("DELETE FROM {} WHERE id = $1", TABLE);
-    let result = client
-        .execute(&stmt, &[&*user_id])
-        .await
-        .map_err(ApiError::from);
+) -> Result<Response, ApiError> {
+    claims.require_admin()?;
 
-    match result {
-        Ok(1) => Ok(ApiResponse::success(
+    match User::delete(&pool, *user_id).await? {
+        false => Err(ApiError::NotFound(format!("User {}", *user_id))),
+        true => Ok(ApiResponse::success(
             user_id.to_string(),
             "User deleted successfully",
             StatusCode::OK,
         )),
-        // update response
-        _ => panic!("Error in delete user"),
     }
 }
@@ -0,0 +1,54 @@
+use crate::{
+    data::database::Pool,
+    models::{
+        response::{ApiError, ApiResponse, AuthError},
+        user::{User, UserAuthRequest, UserAuthResponse},
+    },
+    utils::{
+        bcrypt::{compare_password, hash_password},
+        regex::{regex_email, regex_password},
+    },
+    web::{Json, Response, State, StatusCode},
+};
+
+/// **Login User**
+pub async fn login(
+    pool: State<Pool>,
+    payload: Json<UserAuthRequest>,
+) -> Result<Response, ApiError> {
+    let user = User::find_by_email(&pool, &payload.email)
+        .await?
+        .ok_or(ApiError::Auth(AuthError::EmailWrong))?;
+
+    match compare_password(&payload.password, &user.password)? {
+        false => Err(ApiError::Auth(AuthError::PasswordWrong)),
+        true => Ok(ApiResponse::success(
+            UserAuthResponse::user_to_response(user)?,
+            "Successfully logged in",
+            StatusCode::OK,
+        )),
+    }
+}
+
+/// **Register User**
+pub async fn register(
+    pool: State<Pool>,
+    payload: Json<UserAuthRequest>,
+) -> Result<Response, ApiError> {
+    regex_email(&payload.email)?;
+    regex_password(&payload.password)?;
+    if User::find_by_email(&pool, &payload.email).await?.is_some() {
+        return Err(ApiError::Auth(AuthError::EmailTaken));
+    }
+
+    let name = payload.name.clone().unwrap_or_default();
+    let password_hash = hash_password(&payload.password)?;
+    let user = User::new(name, payload.email.clone(), password_hash);
+    user.insert(&pool).await?;
+
+    Ok(ApiResponse::success(
+        UserAuthResponse::user_to_response(user)?,
+        "User registered successfully",
+        StatusCode::CREATED,
+    ))
+}
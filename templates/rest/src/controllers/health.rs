@@ -0,0 +1,60 @@
+{% if 'cache' in features %}
+use deadpool_redis::{Pool as CachePool, redis::cmd};
+{% endif %}
+use serde::Serialize;
+
+{% if database != 'none' %}
+use crate::data::database::{self, Pool};
+{% endif %}
+use crate::models::response::{ApiError, ApiResponse};
+{% if database != 'none' or 'cache' in features %}
+use crate::web::{Response, State, StatusCode};
+{% else %}
+use crate::web::{Response, StatusCode};
+{% endif %}
+
+#[derive(Serialize)]
+{% if database == 'none' and 'cache' not in features %}
+pub struct Health {}
+{% else %}
+pub struct Health {
+{% if database != 'none' %}
+    pub database: bool,
+{% endif %}
+{% if 'cache' in features %}
+    pub cache: bool,
+{% endif %}
+}
+{% endif %}
+
+/// **Health Check**
+pub async fn health({% if database != 'none' %}pool: State<Pool>{% if 'cache' in features %}, {% endif %}{% endif %}{% if 'cache' in features %}cache: State<CachePool>{% endif %}) -> Result<Response, ApiError> {
+{% if database != 'none' %}
+    database::ping(&pool).await.map_err(ApiError::from)?;
+
+{% endif %}
+{% if 'cache' in features %}
+    let mut connection = cache.get().await.map_err(ApiError::from)?;
+    cmd("PING")
+        .query_async::<String>(&mut connection)
+        .await
+        .map_err(ApiError::from)?;
+
+{% endif %}
+    Ok(ApiResponse::success(
+{% if database != 'none' and 'cache' in features %}
+        Health {
+            database: true,
+            cache: true,
+        },
+{% elif database != 'none' %}
+        Health { database: true },
+{% elif 'cache' in features %}
+        Health { cache: true },
+{% else %}
+        Health {},
+{% endif %}
+        "Service is healthy",
+        StatusCode::OK,
+    ))
+}
@@ -1,2 +1,13 @@
+{% if 'admin' in features %}
 pub mod admin;
-pub mod user;
+{% endif %}
+{% if 'auth' in features %}
+pub mod auth;
+{% endif %}
+pub mod health;
+{% if 'auth' in features %}
+pub mod profile;
+{% endif %}
+{% if 'profile-picture' in features %}
+pub mod profile_picture;
+{% endif %}
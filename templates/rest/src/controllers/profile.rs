@@ -0,0 +1,90 @@
+use crate::{
+    data::database::Pool,
+    models::{
+        response::{ApiError, ApiResponse, AuthError},
+        user::{UpdateProfile, User, UserUpdatePassword},
+    },
+    utils::{
+        bcrypt::{compare_password, hash_password},
+        jwt::Claims,
+        regex::{regex_email, regex_password},
+    },
+    web::{Json, Response, State, StatusCode},
+};
+
+/// **Read User Profile**
+pub async fn get_user_profile(pool: State<Pool>, claims: Claims) -> Result<Response, ApiError> {
+    let user = current_user(&pool, &claims).await?;
+
+    Ok(ApiResponse::success(
+        user,
+        "User retrieved successfully",
+        StatusCode::OK,
+    ))
+}
+
+/// **Update User Profile**
+pub async fn update_user_profile(
+    pool: State<Pool>,
+    claims: Claims,
+    payload: Json<UpdateProfile>,
+) -> Result<Response, ApiError> {
+    regex_email(&payload.email)?;
+    let mut user = current_user(&pool, &claims).await?;
+    if let Some(other) = User::find_by_email(&pool, &payload.email).await?
+        && other.id != user.id
+    {
+        return Err(ApiError::Auth(AuthError::EmailTaken));
+    }
+
+    user.name = payload.name.clone();
+    user.email = payload.email.clone();
+    user.update(&pool).await?;
+
+    Ok(ApiResponse::success(
+        (),
+        "User updated successfully",
+        StatusCode::OK,
+    ))
+}
+
+/// **Update User Password**
+pub async fn update_user_password(
+    pool: State<Pool>,
+    claims: Claims,
+    payload: Json<UserUpdatePassword>,
+) -> Result<Response, ApiError> {
+    let mut user = current_user(&pool, &claims).await?;
+    if !compare_password(&payload.old_password, &user.password)? {
+        return Err(ApiError::Auth(AuthError::PasswordWrong));
+    }
+
+    regex_password(&payload.new_password)?;
+    user.password = hash_password(&payload.new_password)?;
+    user.update(&pool).await?;
+
+    Ok(ApiResponse::success(
+        (),
+        "Password updated successfully",
+        StatusCode::OK,
+    ))
+}
+
+/// **Delete User Profile**
+pub async fn delete_user_profile(pool: State<Pool>, claims: Claims) -> Result<Response, ApiError> {
+    match User::delete(&pool, claims.user_id()?).await? {
+        false => Err(ApiError::NotFound("User".to_string())),
+        true => Ok(ApiResponse::success(
+            (),
+            "Account deleted successfully",
+            StatusCode::OK,
+        )),
+    }
+}
+
+/// The user the token was issued to.
+async fn current_user(pool: &Pool, claims: &Claims) -> Result<User, ApiError> {
+    User::get(pool, claims.user_id()?)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("User".to_string()))
+}
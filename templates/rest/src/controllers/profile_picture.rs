@@ -0,0 +1,63 @@
+use crate::{
+    data::database::Pool,
+    models::{
+        response::{ApiError, ApiResponse},
+        user::User,
+    },
+    utils::jwt::Claims,
+    web::{Json, Response, State, StatusCode},
+};
+
+/// **Read Profile Picture**
+pub async fn get_profile_picture(pool: State<Pool>, claims: Claims) -> Result<Response, ApiError> {
+    let user = current_user(&pool, &claims).await?;
+
+    Ok(ApiResponse::success(
+        user.profile_picture,
+        "User's profile picture retrieved successfully",
+        StatusCode::OK,
+    ))
+}
+
+/// **Add or Replace Profile Picture**
+pub async fn add_or_edit_profile_picture(
+    pool: State<Pool>,
+    claims: Claims,
+    profile_picture: Json<String>,
+) -> Result<Response, ApiError> {
+    set_profile_picture(&pool, &claims, Some(profile_picture.0)).await?;
+    Ok(ApiResponse::success(
+        (),
+        "User's profile picture updated successfully",
+        StatusCode::OK,
+    ))
+}
+
+/// **Delete Profile Picture**
+pub async fn delete_profile_picture(
+    pool: State<Pool>,
+    claims: Claims,
+) -> Result<Response, ApiError> {
+    set_profile_picture(&pool, &claims, None).await?;
+    Ok(ApiResponse::success(
+        (),
+        "User's profile picture deleted successfully",
+        StatusCode::OK,
+    ))
+}
+
+async fn set_profile_picture(
+    pool: &Pool,
+    claims: &Claims,
+    profile_picture: Option<String>,
+) -> Result<(), ApiError> {
+    let mut user = current_user(pool, claims).await?;
+    user.profile_picture = profile_picture;
+    user.update(pool).await
+}
+
+async fn current_user(pool: &Pool, claims: &Claims) -> Result<User, ApiError> {
+    User::get(pool, claims.user_id()?)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("User".to_string()))
+}
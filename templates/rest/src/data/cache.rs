@@ -1,6 +1,6 @@
 use std::env;
 
-use deadpool_redis::{redis::RedisError, Config, Pool};
+use deadpool_redis::{Config, Pool, redis::RedisError};
 use log::debug;
 
 /// Creates a Redis connection pool.
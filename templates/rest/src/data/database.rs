@@ -1,86 +1,155 @@
-use std::{env, sync::Arc};
+{% if database == 'none' %}
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use uuid::Uuid;
+
+// Table names for the store, one `<NAME>_TABLE: &str` per table.
+{% if 'auth' in features %}
+pub static USERS_TABLE: &str = "users";
+{% endif %}
+
+/// The rows of one table by id, each of the table's row type.
+type Rows = HashMap<Uuid, Box<dyn Any + Send + Sync>>;
+
+/// Rows by table and id, kept in memory and lost on restart.
+{% if 'auth' not in features %}
+// For your own handlers and generated resources
+#[allow(dead_code)]
+{% endif %}
+#[derive(Default)]
+pub struct Store {
+    tables: RwLock<HashMap<&'static str, Rows>>,
+}
+
+/// The store, shared by every handler.
+pub type Pool = Arc<Store>;
+
+{% if 'auth' not in features %}
+#[allow(dead_code)]
+{% endif %}
+impl Store {
+    /// Every row of `table`.
+    pub fn all<T: Clone + 'static>(&self, table: &'static str) -> Vec<T> {
+        let tables = self.tables.read().expect("store lock poisoned");
+        tables
+            .get(table)
+            .into_iter()
+            .flat_map(|rows| rows.values())
+            .filter_map(|row| row.downcast_ref::<T>())
+            .cloned()
+            .collect()
+    }
+
+    /// The row of `table` with `id`, if any.
+    pub fn get<T: Clone + 'static>(&self, table: &'static str, id: Uuid) -> Option<T> {
+        let tables = self.tables.read().expect("store lock poisoned");
+        tables
+            .get(table)
+            .and_then(|rows| rows.get(&id))
+            .and_then(|row| row.downcast_ref::<T>())
+            .cloned()
+    }
+
+    /// Insert the row with `id`, replacing any previous one.
+    pub fn put<T: Send + Sync + 'static>(&self, table: &'static str, id: Uuid, row: T) {
+        let mut tables = self.tables.write().expect("store lock poisoned");
+        tables.entry(table).or_default().insert(id, Box::new(row));
+    }
+
+    /// Remove the row with `id`, returning whether there was one.
+    pub fn remove(&self, table: &'static str, id: Uuid) -> bool {
+        let mut tables = self.tables.write().expect("store lock poisoned");
+        tables
+            .get_mut(table)
+            .is_some_and(|rows| rows.remove(&id).is_some())
+    }
+}
+{% else %}
+use std::env;
+{% if database == 'sqlite' %}
+use std::str::FromStr;
+{% endif %}
 
 use log::debug;
-use tokio::sync::Mutex;
-use tokio_postgres::{Client, Error, NoTls};
+{% if database == 'sqlite' %}
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+{% endif %}
 
+/// The database backend.
+pub type Db = sqlx::{{ {"postgres": "Postgres", "sqlite": "Sqlite", "mysql": "MySql"}[database] }};
 
-/// Table names for the database.
+/// A pool of database connections, shared by every handler.
+pub type Pool = sqlx::Pool<Db>;
+
+// Table names for the database, one `<NAME>_TABLE: &str` per table.
+{% if 'auth' in features %}
 pub static USERS_TABLE: &str = "users";
-pub static TODOS_TABLE: &str = "todos";
+{% endif %}
+
+/// A migration's name and SQL, from `migrations/<name>.sql`.
+{% if 'auth' not in features %}
+#[allow(unused_macros)]
+{% endif %}
+macro_rules! migration {
+    ($name:literal) => {
+        (
+            $name,
+            include_str!(concat!("../../migrations/", $name, ".sql")),
+        )
+    };
+}
 
-/// Establishes a connection to the PostgreSQL database.
+/// Migrations run at startup, in order.
+static MIGRATIONS: &[(&str, &str)] = &[
+    // One `migration!("<name>")` per file of `migrations/`
+{% if 'auth' in features %}
+    migration!("0001_create_users"),
+{% endif %}
+];
+
+/// Connects to the database at `DATABASE_URL`.
 ///
 /// # Returns
-/// A `Result` containing the `Client` for executing queries or an error if the connection fails.
+/// A `Result` containing the connection pool or an error if the connection fails.
 ///
 /// # Errors
 /// Returns an `Error` if the database connection cannot be established.
-pub async fn connect() -> Result<Client, Error> {
+pub async fn connect() -> Result<Pool, sqlx::Error> {
     // Fetch the database URL from environment variables.
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
 
     // Log the database URL for debugging purposes (consider masking sensitive data).
     debug!("Database url found: {}", database_url);
 
-    // Attempt to connect to the PostgreSQL database.
-    let (client, connection) = tokio_postgres::connect(&database_url, NoTls).await?;
-
-    // Spawn a separate task to manage the connection and handle potential errors.
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            log::error!("Database connection error: {}", e);
-        }
-    });
-
-    Ok(client)
+{% if database == 'sqlite' %}
+    // Create the database file on first start.
+    let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true);
+    SqlitePoolOptions::new().connect_with(options).await
+{% else %}
+    Pool::connect(&database_url).await
+{% endif %}
 }
 
-/// Creates necessary database tables if they do not exist.
-///
-/// # Parameters
-/// - `client`: A shared and synchronized PostgreSQL client.
-///
-/// # Returns
-/// A `Result` indicating success or failure in creating the tables.
+/// Creates the database tables by running every migration; they must be
+/// safe to run again (`CREATE TABLE IF NOT EXISTS`).
 ///
 /// # Errors
-/// Returns an `Error` if any of the table creation queries fail.
-pub async fn create_tables(client: &Arc<Mutex<Client>>) -> Result<(), Error> {
-    // Log the table creation process.
-    debug!("Creating tables: '{}'", USERS_TABLE);
-
-    // Ensure the pgcrypto extension is available for UUID generation.
-    let extension = "CREATE EXTENSION IF NOT EXISTS pgcrypto;";
-
-    // SQL statement for creating the users table.
-    let create_users_table = format!(
-        "
-    CREATE TABLE IF NOT EXISTS {} (
-        id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-        name VARCHAR(100) NOT NULL,
-        email_hash CHAR(128) NOT NULL UNIQUE,
-        email_encrypt TEXT NOT NULL,
-        nonce VARCHAR(24) NOT NULL,
-        password TEXT NOT NULL,
-        role VARCHAR(5) NOT NULL CHECK (role IN ('admin', 'user')) DEFAULT 'user',
-    );
-    ",
-        USERS_TABLE
-    );
-
-    // Lock the database client to execute queries sequentially.
-    let client = client.lock().await;
-
-    // Execute the extension and table creation queries.
-    client
-        .batch_execute(&extension)
-        .await
-        .expect("Could not pgcrypto create extension");
-    client
-        .batch_execute(&create_users_table)
-        .await
-        .expect("Could not create users table");
+/// Returns an `Error` if any of the migrations fail.
+pub async fn create_tables(pool: &Pool) -> Result<(), sqlx::Error> {
+    for (name, migration) in MIGRATIONS {
+        debug!("Running migration '{}'", name);
+        sqlx::raw_sql(migration).execute(pool).await?;
+    }
+    Ok(())
+}
 
+/// Checks that the database answers.
+pub async fn ping(pool: &Pool) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT 1").execute(pool).await?;
     Ok(())
 }
+{% endif %}
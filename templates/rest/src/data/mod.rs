@@ -1,2 +1,4 @@
+{% if 'cache' in features %}
 pub mod cache;
+{% endif %}
 pub mod database;
@@ -1,9 +1,149 @@
 mod controllers;
+{% if workspace %}
+mod routes;
+
+{% if 'auth' in features or 'rate-limit' in features %}
+use shared::{data, models, utils, web};
+{% else %}
+use shared::{data, models, web};
+{% endif %}
+{% else %}
 mod data;
 mod models;
 mod routes;
+{% if 'auth' in features or 'rate-limit' in features %}
 mod utils;
+{% endif %}
+mod web;
+{% endif %}
+
+{% if framework == 'axum' %}
+use std::{env, net::SocketAddr, time::Instant};
+
+use axum::{
+    extract::Request,
+{% if 'rate-limit' in features %}
+    middleware::{Next, from_fn, from_fn_with_state},
+{% else %}
+    middleware::{Next, from_fn},
+{% endif %}
+    response::Response,
+};
+use tokio::net::TcpListener;
+use tower_http::cors::CorsLayer;
+{% else %}
+use std::env;
+
+use actix_cors::Cors;
+{% if 'rate-limit' in features %}
+use actix_web::middleware::{Logger, from_fn};
+{% else %}
+use actix_web::middleware::Logger;
+{% endif %}
+use actix_web::{App, HttpServer, web::Data};
+{% endif %}
+
+{% if framework == 'axum' %}
+#[tokio::main]
+{% else %}
+#[actix_web::main]
+{% endif %}
+async fn main() -> std::io::Result<()> {
+    dotenvy::dotenv().ok();
+    setup_logger();
+
+{% if database == 'none' %}
+    let pool = data::database::Pool::default();
+{% else %}
+    let pool = data::database::connect()
+        .await
+        .expect("Could not connect to the database");
+    data::database::create_tables(&pool)
+        .await
+        .expect("Could not create the database tables");
+{% endif %}
+{% if 'cache' in features %}
+    let cache = data::cache::pool().expect("Could not create the Redis pool");
+{% endif %}
+{% if 'rate-limit' in features %}
+    let rate_limiter = utils::rate_limiter::RateLimiter::from_env();
+{% endif %}
+
+    let address = env::var("SERVER_ADDRESS").unwrap_or_else(|_| "0.0.0.0:8080".to_string());
+    log::info!("Listening on {}", address);
+
+{% if framework == 'axum' %}
+    let state = web::AppState { pool{% if 'cache' in features %}, cache{% endif %} };
+    let app = routes::router()
+{% if 'rate-limit' in features %}
+        .layer(from_fn_with_state(rate_limiter, utils::rate_limiter::limit))
+{% endif %}
+        .layer(CorsLayer::permissive())
+        .layer(from_fn(log_request))
+        .with_state(state);
+
+    let listener = TcpListener::bind(address).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+{% else %}
+    HttpServer::new(move || {
+        App::new()
+            .app_data(Data::new(pool.clone()))
+{% if 'cache' in features %}
+            .app_data(Data::new(cache.clone()))
+{% endif %}
+{% if 'rate-limit' in features %}
+            .app_data(Data::new(rate_limiter.clone()))
+            .wrap(from_fn(utils::rate_limiter::limit))
+{% endif %}
+            .wrap(Cors::permissive())
+            .wrap(Logger::default())
+            .configure(routes::configure)
+    })
+    .bind(address)?
+    .run()
+    .await
+{% endif %}
+}
+
+/// Log to stdout at the level given by `RUST_LOG` (`info` by default).
+fn setup_logger() {
+    let level = env::var("RUST_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(log::LevelFilter::Info);
+    fern::Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!(
+                "[{} {} {}] {}",
+                chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S"),
+                record.level(),
+                record.target(),
+                message
+            ))
+        })
+        .level(level)
+        .chain(std::io::stdout())
+        .apply()
+        .expect("Could not set up the logger");
+}
+{% if framework == 'axum' %}
 
-fn main() {
-    println!("Hello, world!");
+/// Log every request with its status and duration.
+async fn log_request(request: Request, next: Next) -> Response {
+    let (method, uri) = (request.method().clone(), request.uri().clone());
+    let start = Instant::now();
+    let response = next.run(request).await;
+    log::info!(
+        "\"{} {}\" {} {:.6}",
+        method,
+        uri,
+        response.status().as_u16(),
+        start.elapsed().as_secs_f64()
+    );
+    response
 }
+{% endif %}
@@ -1,2 +1,4 @@
 pub mod response;
+{% if 'auth' in features %}
 pub mod user;
+{% endif %}
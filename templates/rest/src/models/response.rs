@@ -1,14 +1,32 @@
 use std::fmt;
 
-use actix_web::{error::ResponseError, http::StatusCode, HttpResponse, Responder};
+{% if framework == 'axum' %}
+use axum::response::IntoResponse;
+{% else %}
+use actix_web::{HttpResponse, Responder, error::ResponseError};
+{% endif %}
+{% if 'auth' in features %}
 use bcrypt::BcryptError;
-use deadpool_redis::{redis::RedisError, PoolError};
+{% endif %}
+{% if 'cache' in features %}
+use deadpool_redis::{PoolError, redis::RedisError};
+{% endif %}
+{% if 'auth' in features %}
 use jsonwebtoken::errors::Error as JwtError;
+{% endif %}
 use serde::Serialize;
 use serde_json::Error as SerdeJsonError;
-use tokio_postgres::Error as PgError;
+{% if database != 'none' %}
+use sqlx::Error as DbError;
+{% endif %}
 use uuid::Error as UuidError;
 
+{% if framework == 'axum' %}
+use crate::web::{Json, Response, StatusCode};
+{% else %}
+use crate::web::{Response, StatusCode};
+{% endif %}
+
 // API Response
 #[derive(Serialize)]
 pub struct ApiResponse<T> {
@@ -17,10 +35,13 @@ pub struct ApiResponse<T> {
     error: u16,
 }
 
+{% if 'auth' in features %}
 // Define a custom error type
 #[derive(Debug)]
 pub enum AuthError {
+{% if 'admin' in features %}
     AdminsOnly,
+{% endif %}
     MissingHeader,
     InvalidHeader,
     MissingBearer,
@@ -33,7 +54,9 @@ pub enum AuthError {
 impl fmt::Display for AuthError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+{% if 'admin' in features %}
             AuthError::AdminsOnly => write!(f, "Admin only section"),
+{% endif %}
             AuthError::MissingHeader => write!(f, "Authorization header is missing"),
             AuthError::InvalidHeader => write!(f, "Invalid authorization header format"),
             AuthError::MissingBearer => write!(f, "Error striping 'Bearer '"),
@@ -44,73 +67,120 @@ impl fmt::Display for AuthError {
     }
 }
 
+{% endif %}
 // Define a custom error type
 #[derive(Debug)]
 pub enum ApiError {
+{% if 'auth' in features %}
     Jwt(JwtError),
-    OpenSSL(openssl::error::Error),
     Bcrypt(BcryptError),
-    Database(PgError),
+{% endif %}
+{% if database != 'none' %}
+    Database(DbError),
+{% endif %}
+{% if 'cache' in features %}
     Redis(RedisError),
     RedisPool(PoolError),
+{% endif %}
     SerdeJson(SerdeJsonError),
     Uuid(UuidError),
+{% if 'auth' in features %}
     Auth(AuthError),
+{% endif %}
+{% if 'rate-limit' in features %}
     TooManyRequests,
+{% endif %}
+{% if 'auth' in features %}
     RegexValidationError(String),
+{% endif %}
+    // For your own handlers
+    #[allow(dead_code)]
     Custom(String),
+{% if 'auth' not in features %}
+    #[allow(dead_code)]
+{% endif %}
+    NotFound(String),
 }
 
 // Implement `Display` for pretty-printing
 impl fmt::Display for ApiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+{% if 'auth' in features %}
             ApiError::Jwt(e) => write!(f, "JWT error: {}", e),
-            ApiError::OpenSSL(e) => write!(f, "OpenSSL error: {}", e),
             ApiError::Bcrypt(e) => write!(f, "Bcrypt error: {}", e),
+{% endif %}
+{% if database != 'none' %}
             ApiError::Database(e) => write!(f, "Database error: {}", e),
+{% endif %}
+{% if 'cache' in features %}
             ApiError::Redis(e) => write!(f, "Redis error: {}", e),
             ApiError::RedisPool(e) => write!(f, "Redis pool error: {}", e),
+{% endif %}
             ApiError::SerdeJson(e) => write!(f, "Serialization error: {}", e),
             ApiError::Uuid(e) => write!(f, "UUID error: {}", e),
+{% if 'auth' in features %}
             ApiError::Auth(e) => write!(f, "Auth error: {}", e),
+{% endif %}
+{% if 'rate-limit' in features %}
             ApiError::TooManyRequests => write!(f, "Too many requests"),
+{% endif %}
+{% if 'auth' in features %}
             ApiError::RegexValidationError(msg) => write!(f, "Regex validation error: {}", msg),
+{% endif %}
             ApiError::Custom(msg) => write!(f, "Custom error: {}", msg),
+            ApiError::NotFound(msg) => write!(f, "Not found: {}", msg),
         }
     }
 }
 
-// Implement Actix's `ResponseError` for `ApiError`
-impl ResponseError for ApiError {
-    fn status_code(&self) -> StatusCode {
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
         match self {
-            ApiError::Jwt(_) => StatusCode::UNAUTHORIZED, // 401
-            ApiError::OpenSSL(_) => StatusCode::INTERNAL_SERVER_ERROR, // 500
-            ApiError::Bcrypt(_) => StatusCode::INTERNAL_SERVER_ERROR, // 500
-            ApiError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR, // 500
-            ApiError::Redis(_) => StatusCode::INTERNAL_SERVER_ERROR, // 500
-            ApiError::RedisPool(_) => StatusCode::INTERNAL_SERVER_ERROR, // 500
-            ApiError::SerdeJson(_) => StatusCode::BAD_REQUEST, // 400
-            ApiError::Uuid(_) => StatusCode::BAD_REQUEST, // 400
+{% if 'auth' in features %}
+            ApiError::Jwt(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Bcrypt(_) => StatusCode::INTERNAL_SERVER_ERROR,
+{% endif %}
+{% if database != 'none' %}
+            ApiError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+{% endif %}
+{% if 'cache' in features %}
+            ApiError::Redis(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::RedisPool(_) => StatusCode::INTERNAL_SERVER_ERROR,
+{% endif %}
+            ApiError::SerdeJson(_) => StatusCode::BAD_REQUEST,
+            ApiError::Uuid(_) => StatusCode::BAD_REQUEST,
+{% if 'auth' in features %}
             ApiError::Auth(auth_error) => match auth_error {
-                AuthError::AdminsOnly => StatusCode::UNAUTHORIZED, // 401
-                AuthError::MissingHeader => StatusCode::UNAUTHORIZED, // 401
-                AuthError::InvalidHeader => StatusCode::BAD_REQUEST, // 400
-                AuthError::MissingBearer => StatusCode::BAD_REQUEST, // 400
-                AuthError::PasswordWrong => StatusCode::BAD_REQUEST, // 400
-                AuthError::EmailTaken => StatusCode::BAD_REQUEST,  // 400
-                AuthError::EmailWrong => StatusCode::BAD_REQUEST,  // 400
+{% if 'admin' in features %}
+                AuthError::AdminsOnly => StatusCode::UNAUTHORIZED,
+{% endif %}
+                AuthError::MissingHeader => StatusCode::UNAUTHORIZED,
+                AuthError::InvalidHeader => StatusCode::BAD_REQUEST,
+                AuthError::MissingBearer => StatusCode::BAD_REQUEST,
+                AuthError::PasswordWrong => StatusCode::BAD_REQUEST,
+                AuthError::EmailTaken => StatusCode::BAD_REQUEST,
+                AuthError::EmailWrong => StatusCode::BAD_REQUEST,
             },
-            ApiError::TooManyRequests => StatusCode::TOO_MANY_REQUESTS, // 429
-            ApiError::RegexValidationError(_) => StatusCode::BAD_REQUEST, // 400
-            ApiError::Custom(_) => StatusCode::BAD_REQUEST,             // 400
+{% endif %}
+{% if 'rate-limit' in features %}
+            ApiError::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+{% endif %}
+{% if 'auth' in features %}
+            ApiError::RegexValidationError(_) => StatusCode::BAD_REQUEST,
+{% endif %}
+            ApiError::Custom(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
         }
     }
 
-    fn error_response(&self) -> HttpResponse {
-        let error_number = match self {
+    /// The stable number clients can match on, sent as `error`.
+    pub fn code(&self) -> u16 {
+        match self {
+{% if 'admin' in features %}
             ApiError::Auth(AuthError::AdminsOnly) => 1007,
+{% endif %}
+{% if 'auth' in features %}
             ApiError::Auth(AuthError::MissingHeader) => 1001,
             ApiError::Auth(AuthError::InvalidHeader) => 1002,
             ApiError::Auth(AuthError::MissingBearer) => 1003,
@@ -118,29 +188,60 @@ impl ResponseError for ApiError {
             ApiError::Auth(AuthError::EmailTaken) => 1005,
             ApiError::Auth(AuthError::EmailWrong) => 1006,
             ApiError::Jwt(_) => 2001,
-            ApiError::OpenSSL(_) => 2003,
             ApiError::Bcrypt(_) => 2002,
+{% endif %}
+{% if database != 'none' %}
             ApiError::Database(_) => 3001,
+{% endif %}
+{% if 'cache' in features %}
             ApiError::Redis(_) => 3002,
             ApiError::RedisPool(_) => 3003,
+{% endif %}
             ApiError::SerdeJson(_) => 4002,
             ApiError::Uuid(_) => 4001,
+{% if 'rate-limit' in features %}
             ApiError::TooManyRequests => 4290,
+{% endif %}
+{% if 'auth' in features %}
             ApiError::RegexValidationError(_) => 1000,
+{% endif %}
             ApiError::Custom(_) => 5001,
-        };
+            ApiError::NotFound(_) => 4040,
+        }
+    }
 
-        let response = ApiResponse::<()> {
+    fn response(&self) -> Response {
+        ApiResponse::<()> {
             data: None,
             message: self.to_string(),
-            error: error_number,
-        };
+            error: self.code(),
+        }
+        .with_status(self.status())
+    }
+}
 
-        HttpResponse::build(self.status_code()).json(response)
+{% if framework == 'axum' %}
+// Implement Axum's `IntoResponse` for `ApiError`
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        self.response()
     }
 }
+{% else %}
+// Implement Actix's `ResponseError` for `ApiError`
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.status()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        self.response()
+    }
+}
+{% endif %}
 
 // Convert other errors into `ApiError`
+{% if 'auth' in features %}
 impl From<JwtError> for ApiError {
     fn from(err: JwtError) -> Self {
         log::error!("JWT error: {}", err);
@@ -148,13 +249,6 @@ impl From<JwtError> for ApiError {
     }
 }
 
-impl From<openssl::error::Error> for ApiError {
-    fn from(err: openssl::error::Error) -> Self {
-        log::error!("OpenSSL error: {}", err);
-        ApiError::OpenSSL(err)
-    }
-}
-
 impl From<BcryptError> for ApiError {
     fn from(err: BcryptError) -> Self {
         log::error!("Bcrypt error: {}", err);
@@ -162,13 +256,17 @@ impl From<BcryptError> for ApiError {
     }
 }
 
-impl From<PgError> for ApiError {
-    fn from(err: PgError) -> Self {
-        log::error!("Postgres error: {}", err);
+{% endif %}
+{% if database != 'none' %}
+impl From<DbError> for ApiError {
+    fn from(err: DbError) -> Self {
+        log::error!("Database error: {}", err);
         ApiError::Database(err)
     }
 }
 
+{% endif %}
+{% if 'cache' in features %}
 impl From<RedisError> for ApiError {
     fn from(err: RedisError) -> Self {
         log::error!("Redis error: {}", err);
@@ -183,6 +281,7 @@ impl From<PoolError> for ApiError {
     }
 }
 
+{% endif %}
 impl From<SerdeJsonError> for ApiError {
     fn from(err: SerdeJsonError) -> Self {
         log::error!("SerdeJson error: {}", err);
@@ -197,6 +296,7 @@ impl From<UuidError> for ApiError {
     }
 }
 
+{% if 'auth' in features %}
 impl From<AuthError> for ApiError {
     fn from(err: AuthError) -> Self {
         log::error!("Auth error: {}", err);
@@ -204,20 +304,35 @@ impl From<AuthError> for ApiError {
     }
 }
 
+{% endif %}
 // Success response method for API responses
-impl<T> ApiResponse<T> {
-    pub fn success(data: T, message: &str, status: StatusCode) -> HttpResponse
-    where
-        T: Serialize,
-    {
-        HttpResponse::build(status).json(Self {
+impl<T: Serialize> ApiResponse<T> {
+    pub fn success(data: T, message: &str, status: StatusCode) -> Response {
+        ApiResponse {
             data: Some(data),
             message: message.to_string(),
             error: 0,
-        })
+        }
+        .with_status(status)
+    }
+
+    fn with_status(self, status: StatusCode) -> Response {
+{% if framework == 'axum' %}
+        (status, Json(self)).into_response()
+{% else %}
+        HttpResponse::build(status).json(self)
+{% endif %}
     }
 }
 
+{% if framework == 'axum' %}
+// Implement `IntoResponse` for `ApiResponse<T>` to convert the success response to JSON
+impl<T: Serialize> IntoResponse for ApiResponse<T> {
+    fn into_response(self) -> Response {
+        self.with_status(StatusCode::OK)
+    }
+}
+{% else %}
 // Implement `Responder` for `ApiResponse<T>` to convert the success response to JSON
 impl<T> Responder for ApiResponse<T>
 where
@@ -226,6 +341,7 @@ where
     type Body = actix_web::body::BoxBody;
 
     fn respond_to(self, _: &actix_web::HttpRequest) -> HttpResponse<Self::Body> {
-        HttpResponse::Ok().json(self)
+        self.with_status(StatusCode::OK)
     }
 }
+{% endif %}
@@ -1,21 +1,35 @@
 use log::debug;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::Row;
+{% if database != 'none' %}
+use sqlx::{FromRow, QueryBuilder};
+{% endif %}
 use uuid::Uuid;
 
-use crate::utils::{encryption::sha_encrypt_string, jwt::generate_jwt};
+{% if database == 'none' %}
+use crate::data::database::{Pool, USERS_TABLE};
+{% else %}
+use crate::data::database::{Db, Pool, USERS_TABLE};
+{% endif %}
+use crate::models::response::ApiError;
+use crate::utils::jwt::generate_jwt;
 
-#[derive(Debug, Serialize, Deserialize)]
+{% if database == 'none' %}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+{% else %}
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+{% endif %}
 pub struct User {
     pub id: Uuid,
     pub name: String,
-    pub email_hash: String,
-    pub email_encrypt: String,
-    pub nonce: String,
+    pub email: String,
+    #[serde(skip_serializing)]
     pub password: String,
     pub role: String, // 'admin' or 'user'
+{% if 'profile-picture' in features %}
     pub profile_picture: Option<String>,
+{% endif %}
 }
+{% if 'admin' in features %}
 
 #[derive(Debug, Deserialize)]
 pub struct CreateUser {
@@ -23,12 +37,14 @@ pub struct CreateUser {
     pub email: String,
     pub password: String,
 }
+{% endif %}
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateProfile {
     pub name: String,
     pub email: String,
 }
+{% if 'admin' in features %}
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateUser {
@@ -36,25 +52,138 @@ pub struct UpdateUser {
     pub email: String,
     pub password: String,
 }
+{% endif %}
 
 impl User {
-    pub fn from_row(row: &Row) -> Self {
-        debug!("Parsing row to user model");
+    /// A new user with the `user` role; `password` must already be hashed.
+    pub fn new(name: String, email: String, password: String) -> Self {
+        debug!("Creating user model");
         User {
-            id: row.get("id"),
-            name: row.get("name"),
-            email_hash: row.get("email_hash"),
-            // email_hash: String::new(),
-            email_encrypt: row.get("email_encrypt"),
-            // email_encrypt: String::new(),
-            nonce: row.get("nonce"),
-            // nonce: String::new(),
-            password: row.get("password"),
-            // password: String::new(),
-            role: row.get("role"),
-            profile_picture: row.get("profile_picture"),
+            id: Uuid::new_v4(),
+            name,
+            email,
+            password,
+            role: "user".to_string(),
+{% if 'profile-picture' in features %}
+            profile_picture: None,
+{% endif %}
         }
     }
+{% if database == 'none' %}
+{% if 'admin' in features %}
+
+    pub async fn all(pool: &Pool) -> Result<Vec<User>, ApiError> {
+        Ok(pool.all(USERS_TABLE))
+    }
+{% endif %}
+
+    pub async fn get(pool: &Pool, id: Uuid) -> Result<Option<User>, ApiError> {
+        Ok(pool.get(USERS_TABLE, id))
+    }
+
+    pub async fn find_by_email(pool: &Pool, email: &str) -> Result<Option<User>, ApiError> {
+        let users: Vec<User> = pool.all(USERS_TABLE);
+        Ok(users.into_iter().find(|user| user.email == email))
+    }
+
+    pub async fn insert(&self, pool: &Pool) -> Result<(), ApiError> {
+        pool.put(USERS_TABLE, self.id, self.clone());
+        Ok(())
+    }
+
+    pub async fn update(&self, pool: &Pool) -> Result<(), ApiError> {
+        pool.put(USERS_TABLE, self.id, self.clone());
+        Ok(())
+    }
+
+    /// Returns whether the user existed.
+    pub async fn delete(pool: &Pool, id: Uuid) -> Result<bool, ApiError> {
+        Ok(pool.remove(USERS_TABLE, id))
+    }
+{% else %}
+{% if 'admin' in features %}
+
+    pub async fn all(pool: &Pool) -> Result<Vec<User>, ApiError> {
+        let mut query = QueryBuilder::<Db>::new(format!("SELECT * FROM {}", USERS_TABLE));
+        query
+            .build_query_as()
+            .fetch_all(pool)
+            .await
+            .map_err(ApiError::from)
+    }
+{% endif %}
+
+    pub async fn get(pool: &Pool, id: Uuid) -> Result<Option<User>, ApiError> {
+        let mut query =
+            QueryBuilder::<Db>::new(format!("SELECT * FROM {} WHERE id = ", USERS_TABLE));
+        query.push_bind(id);
+        query
+            .build_query_as()
+            .fetch_optional(pool)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    pub async fn find_by_email(pool: &Pool, email: &str) -> Result<Option<User>, ApiError> {
+        let mut query =
+            QueryBuilder::<Db>::new(format!("SELECT * FROM {} WHERE email = ", USERS_TABLE));
+        query.push_bind(email);
+        query
+            .build_query_as()
+            .fetch_optional(pool)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    pub async fn insert(&self, pool: &Pool) -> Result<(), ApiError> {
+        let mut query = QueryBuilder::<Db>::new(format!(
+            "INSERT INTO {} (id, name, email, password, role{{ ', profile_picture' if 'profile-picture' in features }}) VALUES (",
+            USERS_TABLE
+        ));
+        query
+            .separated(", ")
+            .push_bind(self.id)
+            .push_bind(&self.name)
+            .push_bind(&self.email)
+            .push_bind(&self.password)
+{% if 'profile-picture' in features %}
+            .push_bind(&self.role)
+            .push_bind(&self.profile_picture);
+{% else %}
+            .push_bind(&self.role);
+{% endif %}
+        query.push(")");
+        query.build().execute(pool).await.map_err(ApiError::from)?;
+        Ok(())
+    }
+
+    pub async fn update(&self, pool: &Pool) -> Result<(), ApiError> {
+        let mut query = QueryBuilder::<Db>::new(format!("UPDATE {} SET ", USERS_TABLE));
+        let mut fields = query.separated(", ");
+        fields.push("name = ").push_bind_unseparated(&self.name);
+        fields.push("email = ").push_bind_unseparated(&self.email);
+        fields
+            .push("password = ")
+            .push_bind_unseparated(&self.password);
+        fields.push("role = ").push_bind_unseparated(&self.role);
+{% if 'profile-picture' in features %}
+        fields
+            .push("profile_picture = ")
+            .push_bind_unseparated(&self.profile_picture);
+{% endif %}
+        query.push(" WHERE id = ").push_bind(self.id);
+        query.build().execute(pool).await.map_err(ApiError::from)?;
+        Ok(())
+    }
+
+    /// Returns whether the user existed.
+    pub async fn delete(pool: &Pool, id: Uuid) -> Result<bool, ApiError> {
+        let mut query = QueryBuilder::<Db>::new(format!("DELETE FROM {} WHERE id = ", USERS_TABLE));
+        query.push_bind(id);
+        let result = query.build().execute(pool).await.map_err(ApiError::from)?;
+        Ok(result.rows_affected() > 0)
+    }
+{% endif %}
 }
 
 #[derive(Debug, Deserialize)]
@@ -62,7 +191,6 @@ pub struct UserAuthRequest {
     pub name: Option<String>,
     pub email: String,
     pub password: String,
-    pub role: Option<String>, // 'admin' or 'user'
 }
 
 #[derive(Debug, Serialize)]
@@ -72,24 +200,10 @@ pub struct UserAuthResponse {
 }
 
 impl UserAuthResponse {
-    pub fn user_to_response(user: User) -> Self {
+    pub fn user_to_response(user: User) -> Result<Self, jsonwebtoken::errors::Error> {
         debug!("Parsing user to response model");
-        let user_id = user.id;
-        let role = sha_encrypt_string(user.role.clone()).expect("Error encrypting role");
-        let token = generate_jwt(user_id, role).expect("Token error");
-        UserAuthResponse { user, token }
-    }
-
-    pub fn row_to_response(row: Row) -> Self {
-        debug!("Parsing row to response model");
-        let user_id: Uuid = row.get("id");
-        let role: String = row.get("role");
-        let role = sha_encrypt_string(role).expect("Error encrypting role");
-        let token = generate_jwt(user_id, role).expect("Token error");
-        UserAuthResponse {
-            user: User::from_row(&row),
-            token,
-        }
+        let token = generate_jwt(user.id, user.role.clone())?;
+        Ok(UserAuthResponse { user, token })
     }
 }
 
@@ -1,9 +1,26 @@
+{% if framework == 'axum' %}
+use axum::{Router, routing::get};
+
+use crate::{
+    controllers::admin::user::{create_user, delete_user, get_user, get_users, update_user},
+    web::AppState,
+};
+
+pub fn user_routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/users/", get(get_users).post(create_user))
+        .route(
+            "/admin/users/{id}",
+            get(get_user).put(update_user).delete(delete_user),
+        )
+}
+{% else %}
 use actix_web::{
-    web::{delete, get, post, put, scope},
     Scope,
+    web::{delete, get, post, put, scope},
 };
 
-use crate::handlers::admin::{create_user, delete_user, get_user, get_users, update_user};
+use crate::controllers::admin::user::{create_user, delete_user, get_user, get_users, update_user};
 
 pub fn user_routes() -> Scope {
     scope("/admin/users")
@@ -13,3 +30,4 @@ pub fn user_routes() -> Scope {
         .route("/{id}", put().to(update_user))
         .route("/{id}", delete().to(delete_user))
 }
+{% endif %}
@@ -1,11 +1,59 @@
+{% if framework == 'axum' %}
+use axum::{
+    Router,
+    routing::{delete, get, post, put},
+};
+
+{% if 'profile-picture' in features %}
+use crate::controllers::profile_picture::{
+    add_or_edit_profile_picture, delete_profile_picture, get_profile_picture,
+};
+{% endif %}
+use crate::{
+    controllers::{
+        auth::{login, register},
+        profile::{
+            delete_user_profile, get_user_profile, update_user_password, update_user_profile,
+        },
+    },
+    web::AppState,
+};
+
+pub fn auth_routes() -> Router<AppState> {
+    Router::new()
+        .route("/auth/login", post(login))
+        .route("/auth/register", post(register))
+}
+
+pub fn profile_routes() -> Router<AppState> {
+    Router::new()
+        .route("/profile/", get(get_user_profile))
+        .route("/profile/update", put(update_user_profile))
+        .route("/profile/update-password", put(update_user_password))
+        .route("/profile/delete", delete(delete_user_profile))
+{% if 'profile-picture' in features %}
+        .route(
+            "/profile/picture",
+            get(get_profile_picture)
+                .put(add_or_edit_profile_picture)
+                .delete(delete_profile_picture),
+        )
+{% endif %}
+}
+{% else %}
 use actix_web::{
-    web::{delete, get, post, put, scope},
     Scope,
+    web::{delete, get, post, put, scope},
 };
 
-use crate::controllers::auth::{
-    add_or_edit_profile_picture, delete_profile_picture, delete_user_profile, get_profile_picture,
-    get_user_profile, login, register, update_user_password, update_user_profile,
+{% if 'profile-picture' in features %}
+use crate::controllers::profile_picture::{
+    add_or_edit_profile_picture, delete_profile_picture, get_profile_picture,
+};
+{% endif %}
+use crate::controllers::{
+    auth::{login, register},
+    profile::{delete_user_profile, get_user_profile, update_user_password, update_user_profile},
 };
 
 pub fn auth_routes() -> Scope {
@@ -13,23 +61,17 @@ pub fn auth_routes() -> Scope {
         .route("/login", post().to(login))
         .route("/register", post().to(register))
 }
+
 pub fn profile_routes() -> Scope {
     scope("/profile")
         .route("/", get().to(get_user_profile))
         .route("/update", put().to(update_user_profile))
         .route("/update-password", put().to(update_user_password))
         .route("/delete", delete().to(delete_user_profile))
-        // .route("/profile-picture/view", get().to(get_profile_picture))
-        // .route(
-        //     "/profile-picture/upload",
-        //     post().to(add_or_edit_profile_picture),
-        // )
-        // .route(
-        //     "/profile-picture/edit",
-        //     put().to(add_or_edit_profile_picture),
-        // )
-        // .route(
-        //     "/profile-picture/delete",
-        //     delete().to(delete_profile_picture),
-        // )
+{% if 'profile-picture' in features %}
+        .route("/picture", get().to(get_profile_picture))
+        .route("/picture", put().to(add_or_edit_profile_picture))
+        .route("/picture", delete().to(delete_profile_picture))
+{% endif %}
 }
+{% endif %}
@@ -0,0 +1,20 @@
+{% if framework == 'axum' %}
+use axum::{Router, routing::get};
+
+use crate::{controllers::health::health, web::AppState};
+
+pub fn health_routes() -> Router<AppState> {
+    Router::new().route("/health", get(health))
+}
+{% else %}
+use actix_web::{
+    Scope,
+    web::{get, scope},
+};
+
+use crate::controllers::health::health;
+
+pub fn health_routes() -> Scope {
+    scope("/health").route("", get().to(health))
+}
+{% endif %}
@@ -1,2 +1,43 @@
+{% if framework == 'axum' %}
+use axum::Router;
+
+use crate::web::AppState;
+{% else %}
+use actix_web::web::ServiceConfig;
+{% endif %}
+
+{% if 'admin' in features %}
 pub mod admin;
+{% endif %}
+{% if 'auth' in features %}
 pub mod auth;
+{% endif %}
+pub mod health;
+
+{% if framework == 'axum' %}
+/// Every route of the app.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        // One `.merge(<module>::<module>_routes())` per route module
+        .merge(health::health_routes())
+{% if 'auth' in features %}
+        .merge(auth::auth_routes())
+        .merge(auth::profile_routes())
+{% endif %}
+{% if 'admin' in features %}
+        .merge(admin::user_routes())
+{% endif %}
+}
+{% else %}
+/// Mounts every route scope on the app.
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(health::health_routes());
+{% if 'auth' in features %}
+    cfg.service(auth::auth_routes());
+    cfg.service(auth::profile_routes());
+{% endif %}
+{% if 'admin' in features %}
+    cfg.service(admin::user_routes());
+{% endif %}
+}
+{% endif %}
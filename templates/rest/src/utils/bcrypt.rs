@@ -23,7 +23,10 @@ pub fn compare_password(password: &str, hash: &str) -> Result<bool, ApiError> {
             Ok(is_equal)
         }
         Err(e) => {
-            debug!("comparing passwords: '{}' and '{}'\nError: {}", password, hash, e);
+            debug!(
+                "comparing passwords: '{}' and '{}'\nError: {}",
+                password, hash, e
+            );
             error!("Bcrypt error: {}", e);
             Err(ApiError::Bcrypt(e))
         }
@@ -1,18 +1,29 @@
-use std::env;
+{% if framework == 'axum' %}
+use std::{env, str::FromStr, sync::LazyLock};
 
-use actix_web::http::header::HeaderMap;
+use axum::{
+    extract::FromRequestParts,
+    http::{HeaderMap, request::Parts},
+};
+{% else %}
+use std::{
+    env,
+    future::{Ready, ready},
+    str::FromStr,
+    sync::LazyLock,
+};
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload, http::header::HeaderMap};
+{% endif %}
 use chrono::{Duration, Utc};
-use dotenvy::dotenv;
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use serde::{Deserialize, Serialize};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use log::debug;
-use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::models::response::AuthError;
+use crate::models::response::{ApiError, AuthError};
 
-static SECRET_KEY: Lazy<String> = Lazy::new(|| {
-    dotenv().ok();
+static SECRET_KEY: LazyLock<String> = LazyLock::new(|| {
     debug!("Fetching jwt SECRET_KEY...");
     env::var("SECRET_KEY").expect("SECRET_KEY must be set")
 });
@@ -24,6 +35,50 @@ pub struct Claims {
     pub exp: usize,
 }
 
+impl Claims {
+    /// The claims of the bearer token in the `Authorization` header.
+    fn from_headers(headers: &HeaderMap) -> Result<Self, ApiError> {
+        let token = extract_token(headers)?;
+        validate_jwt(&token).map_err(ApiError::from)
+    }
+
+    /// The id of the authenticated user.
+    pub fn user_id(&self) -> Result<Uuid, ApiError> {
+        Uuid::from_str(&self.sub).map_err(ApiError::from)
+    }
+{% if 'admin' in features %}
+
+    /// Fails unless the authenticated user is an admin.
+    pub fn require_admin(&self) -> Result<(), ApiError> {
+        match self.role == "admin" {
+            true => Ok(()),
+            false => Err(ApiError::Auth(AuthError::AdminsOnly)),
+        }
+    }
+{% endif %}
+}
+
+/// Extracts and validates the bearer token, so handlers taking `Claims`
+/// only run for authenticated requests.
+{% if framework == 'axum' %}
+impl<S: Send + Sync> FromRequestParts<S> for Claims {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Claims::from_headers(&parts.headers)
+    }
+}
+{% else %}
+impl FromRequest for Claims {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Claims::from_headers(request.headers()))
+    }
+}
+{% endif %}
+
 pub fn generate_jwt(user_id: Uuid, role: String) -> Result<String, jsonwebtoken::errors::Error> {
     let expiration = Utc::now()
         .checked_add_signed(Duration::minutes(60))
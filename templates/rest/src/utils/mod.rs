@@ -1,3 +1,10 @@
+{% if 'auth' in features %}
 pub mod bcrypt;
 pub mod jwt;
+{% endif %}
+{% if 'rate-limit' in features %}
+pub mod rate_limiter;
+{% endif %}
+{% if 'auth' in features %}
 pub mod regex;
+{% endif %}
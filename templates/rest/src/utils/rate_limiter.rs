@@ -1,13 +1,33 @@
 use std::{
     collections::HashMap,
+    env,
+{% if framework == 'axum' %}
+    net::{IpAddr, SocketAddr},
+{% else %}
+    net::IpAddr,
+{% endif %}
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
-use actix_web::{web::Data, HttpRequest, HttpResponse};
+{% if framework == 'axum' %}
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+{% else %}
+use actix_web::{
+    Error,
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web::Data,
+};
+{% endif %}
 use log::{debug, info, warn};
 
-use super::response::ApiError;
+use crate::models::response::ApiError;
 
 #[derive(Clone)]
 pub struct RateLimiter {
@@ -24,6 +44,22 @@ impl RateLimiter {
             window,
         }
     }
+
+    /// Reads `RATE_LIMIT_REQUESTS` and `RATE_LIMIT_WINDOW_SECS`, defaulting
+    /// to 100 requests per minute.
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: u64| {
+            env::var(key)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default)
+        };
+        RateLimiter::new(
+            read("RATE_LIMIT_REQUESTS", 100) as usize,
+            Duration::from_secs(read("RATE_LIMIT_WINDOW_SECS", 60)),
+        )
+    }
+
     pub fn is_allowed(&self, ip: &str) -> bool {
         debug!("Checking if ip: '{}' is allowed", ip);
         let mut requests = self.requests.lock().unwrap();
@@ -32,37 +68,44 @@ impl RateLimiter {
         if now.duration_since(entry.1) > self.window {
             *entry = (1, now);
             true
+        } else if entry.0 < self.max_requests {
+            entry.0 += 1;
+            info!("Ip: '{}' allowed", ip);
+            true
         } else {
-            if entry.0 < self.max_requests {
-                entry.0 += 1;
-                info!("Ip: '{}' allowed", ip);
-                true
-            } else {
-                warn!("Ip: '{}' not allowed", ip);
-                false
-            }
+            warn!("Ip: '{}' not allowed", ip);
+            false
         }
     }
-    pub fn check_rate_limit(&self, req: &HttpRequest) -> Result<(), ApiError> {
-        if let Some(peer_addr) = req.peer_addr() {
-            let ip = peer_addr.ip().to_string();
-            if !self.is_allowed(&ip) {
-                return Err(ApiError::TooManyRequests);
-            }
+
+    pub fn check_rate_limit(&self, ip: IpAddr) -> Result<(), ApiError> {
+        match self.is_allowed(&ip.to_string()) {
+            true => Ok(()),
+            false => Err(ApiError::TooManyRequests),
         }
-        Ok(())
     }
 }
 
-pub async fn index(req: HttpRequest, data: Data<RateLimiter>) -> Result<HttpResponse, ApiError> {
-    data.check_rate_limit(&req).map_err(ApiError::from)?;
-    Ok(HttpResponse::Ok().body("Hello, World!"))
+/// Middleware rejecting clients over the limit with `429 Too Many Requests`.
+{% if framework == 'axum' %}
+pub async fn limit(
+    State(limiter): State<RateLimiter>,
+    ConnectInfo(peer_addr): ConnectInfo<SocketAddr>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    limiter.check_rate_limit(peer_addr.ip())?;
+    Ok(next.run(req).await)
 }
-
-use std::time::Duration;
-
-use crate::models::rate_limiter::RateLimiter;
-
-pub fn rate_limiter() -> RateLimiter {
-    RateLimiter::new(100, Duration::from_secs(60))
+{% else %}
+pub async fn limit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let limiter = req.app_data::<Data<RateLimiter>>();
+    if let (Some(limiter), Some(peer_addr)) = (limiter, req.peer_addr()) {
+        limiter.check_rate_limit(peer_addr.ip())?;
+    }
+    next.call(req).await
 }
+{% endif %}
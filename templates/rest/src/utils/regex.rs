@@ -11,7 +11,7 @@ pub fn regex_email(email: &str) -> Result<(), ApiError> {
         })?;
 
     match email_regex.is_match(email) {
-        false => return Err(ApiError::RegexValidationError("email invalid".to_string())),
+        false => Err(ApiError::RegexValidationError("email invalid".to_string())),
         true => Ok(()),
     }
 }
@@ -23,11 +23,9 @@ pub fn regex_password(password: &str) -> Result<(), ApiError> {
     })?;
 
     match password_regex.is_match(password) {
-        false => {
-            return Err(ApiError::RegexValidationError(
-                "password invalid".to_string(),
-            ))
-        }
+        false => Err(ApiError::RegexValidationError(
+            "password invalid".to_string(),
+        )),
         true => Ok(()),
     }
 }
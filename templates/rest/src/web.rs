@@ -0,0 +1,38 @@
+//! The web framework's types under the names controllers use, so the same
+//! handlers serve both the actix-web and the axum variant.
+{% if framework == 'axum' %}
+
+use axum::extract::FromRef;
+{% if 'cache' in features %}
+use deadpool_redis::Pool as CachePool;
+{% endif %}
+
+use crate::data::database::Pool;
+
+// Not every set of features needs all of them, but generated resources do
+#[allow(unused_imports)]
+pub use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+};
+
+/// Everything handlers can extract with `State<T>`.
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub pool: Pool,
+{% if 'cache' in features %}
+    pub cache: CachePool,
+{% endif %}
+}
+{% else %}
+
+// Not every set of features needs all of them, but generated resources do
+#[allow(unused_imports)]
+pub use actix_web::{
+    HttpResponse as Response,
+    http::StatusCode,
+    web::{Data as State, Json, Path},
+};
+{% endif %}
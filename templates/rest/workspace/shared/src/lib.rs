@@ -0,0 +1,8 @@
+//! What every service of the workspace uses: the database, the models and
+//! the framework types handlers are written against.
+pub mod data;
+pub mod models;
+{% if 'auth' in features or 'rate-limit' in features %}
+pub mod utils;
+{% endif %}
+pub mod web;
@@ -0,0 +1,67 @@
+use std::{env, time::Duration};
+
+{% if database == 'none' %}
+use shared::data::database::Pool;
+{% else %}
+use shared::data::database::{self, Pool};
+{% endif %}
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+    setup_logger();
+
+{% if database == 'none' %}
+    let pool = Pool::default();
+{% else %}
+    let pool = database::connect()
+        .await
+        .expect("Could not connect to the database");
+{% endif %}
+
+    let seconds = env::var("WORKER_INTERVAL_SECS")
+        .ok()
+        .and_then(|seconds| seconds.parse().ok())
+        .unwrap_or(60);
+    log::info!("Running jobs every {} seconds", seconds);
+
+    let mut interval = tokio::time::interval(Duration::from_secs(seconds));
+    loop {
+        interval.tick().await;
+        run_jobs(&pool).await;
+    }
+}
+
+/// One round of background work; add jobs here.
+async fn run_jobs(pool: &Pool) {
+{% if database == 'none' %}
+    let _ = pool;
+    log::debug!("No jobs to run");
+{% else %}
+    if let Err(e) = database::ping(pool).await {
+        log::error!("Database unreachable: {}", e);
+    }
+{% endif %}
+}
+
+/// Log to stdout at the level given by `RUST_LOG` (`info` by default).
+fn setup_logger() {
+    let level = env::var("RUST_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(log::LevelFilter::Info);
+    fern::Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!(
+                "[{} {} {}] {}",
+                chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S"),
+                record.level(),
+                record.target(),
+                message
+            ))
+        })
+        .level(level)
+        .chain(std::io::stdout())
+        .apply()
+        .expect("Could not set up the logger");
+}
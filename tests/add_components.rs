@@ -0,0 +1,199 @@
+//! `add` on freshly generated projects.
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{Scratch, cargo_check};
+
+/// Every component `add` offers for the `rest` template.
+const COMPONENTS: &[&str] = &[
+    "auth",
+    "admin",
+    "cache",
+    "rate-limit",
+    "profile-picture",
+    "ci",
+    "infra",
+];
+
+/// The flags generating the project with `component` from the start.
+fn with(component: &str) -> Vec<&str> {
+    match component {
+        "ci" => vec!["--features", "", "--with-ci"],
+        "infra" => vec!["--features", "", "--with-infra"],
+        feature => vec!["--features", feature],
+    }
+}
+
+/// Every file below `dir` but the lock files, by relative path.
+fn files(dir: &Path) -> Vec<(String, Vec<u8>)> {
+    let mut found = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current).unwrap() {
+            let path = entry.unwrap().path();
+            let relative = path
+                .strip_prefix(dir)
+                .unwrap()
+                .to_string_lossy()
+                .into_owned();
+            if path.is_dir() {
+                pending.push(path);
+            } else if !relative.starts_with(".woragis") {
+                found.push((relative, fs::read(&path).unwrap()));
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/// The dependency tables of a manifest, whatever order they are written in.
+fn dependencies(cargo: &[u8]) -> toml::Table {
+    let mut cargo: toml::Table = std::str::from_utf8(cargo).unwrap().parse().unwrap();
+    cargo.remove("package");
+    cargo
+}
+
+#[test]
+fn adding_a_component_matches_generating_with_it() {
+    let scratch = Scratch::new("add-matches");
+    for framework in ["actix", "axum"] {
+        for db in ["postgres", "none"] {
+            for component in COMPONENTS {
+                let label = format!("{} with {} and {}", component, framework, db);
+                let (added, generated) = (scratch.join("added"), scratch.join("generated"));
+                let _ = fs::remove_dir_all(&added);
+                let _ = fs::remove_dir_all(&generated);
+                let options = ["--author", "Test", "--framework", framework, "--db", db];
+
+                let mut args = vec!["demo", "--path", "added", "--features", ""];
+                args.extend(options);
+                scratch.ok(&args);
+                let output = scratch.run(&added.join("demo"), &["add", component]);
+                assert!(output.status.success(), "{}: {:?}", label, output);
+
+                let mut args = vec!["demo", "--path", "generated"];
+                args.extend(options);
+                args.extend(with(component));
+                scratch.ok(&args);
+
+                let (added, generated) =
+                    (files(&added.join("demo")), files(&generated.join("demo")));
+                let paths = |files: &[(String, Vec<u8>)]| {
+                    files
+                        .iter()
+                        .map(|(path, _)| path.clone())
+                        .collect::<Vec<_>>()
+                };
+                assert_eq!(paths(&added), paths(&generated), "{}", label);
+                for ((path, added), (_, generated)) in added.iter().zip(&generated) {
+                    if path == "Cargo.toml" {
+                        assert_eq!(dependencies(added), dependencies(generated), "{}", label);
+                    } else {
+                        assert!(
+                            added == generated,
+                            "{}: {} differs:\n{}",
+                            label,
+                            path,
+                            String::from_utf8_lossy(added)
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn add_merges_around_the_users_changes() {
+    let scratch = Scratch::new("add-merge");
+    scratch.ok(&[
+        "demo",
+        "--author",
+        "Test",
+        "--framework",
+        "axum",
+        "--features",
+        "",
+    ]);
+    let project = scratch.join("demo");
+    let main = project.join("src/main.rs");
+
+    // A change away from what the component touches is kept
+    let original = fs::read_to_string(&main).unwrap();
+    let edited = format!("// Our own notes\n{}", original);
+    fs::write(&main, &edited).unwrap();
+    let output = scratch.run(&project, &["add", "rate-limit"]);
+    assert!(output.status.success(), "{:?}", output);
+    let merged = fs::read_to_string(&main).unwrap();
+    assert!(merged.starts_with("// Our own notes\n"), "{}", merged);
+    assert!(merged.contains("from_fn_with_state"), "{}", merged);
+    assert!(!merged.contains("<<<<<<<"), "{}", merged);
+    assert_eq!(merged.matches("middleware::{").count(), 1, "{}", merged);
+
+    // A change to the very line the component rewrites conflicts
+    let readme = project.join("README.md");
+    let text = fs::read_to_string(&readme).unwrap();
+    let health = text
+        .lines()
+        .find(|line| line.starts_with("| `/health`"))
+        .unwrap()
+        .to_string();
+    let text = text.replace(&health, "| `/health` | `GET` | | Is it up? |");
+    fs::write(&readme, text).unwrap();
+    let output = scratch.run(&project, &["add", "cache"]);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("conflict     README.md"), "{}", stdout);
+    assert!(stdout.contains("have conflicts"), "{}", stdout);
+    let conflicted = fs::read_to_string(&readme).unwrap();
+    assert!(conflicted.contains("<<<<<<<"), "{}", conflicted);
+    assert!(conflicted.contains("Is it up?"), "{}", conflicted);
+    assert!(conflicted.contains(&health), "{}", conflicted);
+    assert!(
+        conflicted.contains("the database and Redis"),
+        "{}",
+        conflicted
+    );
+}
+
+/// Adds every component to a fresh project of each framework and runs
+/// `cargo check` on it. Needs the crates of the generated projects, from the
+/// network or the local registry; run with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn added_components_compile() {
+    let scratch = Scratch::new("add-compile");
+    let mut failures = Vec::new();
+    for framework in ["actix", "axum"] {
+        for db in ["postgres", "none"] {
+            for component in COMPONENTS {
+                let dir = scratch.join("demo");
+                let _ = fs::remove_dir_all(&dir);
+                scratch.ok(&[
+                    "demo",
+                    "--author",
+                    "Test",
+                    "--framework",
+                    framework,
+                    "--db",
+                    db,
+                    "--features",
+                    "",
+                ]);
+                let output = scratch.run(&dir, &["add", component]);
+                assert!(output.status.success(), "{:?}", output);
+                if let Err(errors) = cargo_check(&dir) {
+                    failures.push(format!(
+                        "{} with {} and {}:\n{}",
+                        component, framework, db, errors
+                    ));
+                }
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
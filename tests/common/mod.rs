@@ -0,0 +1,136 @@
+//! Helpers shared by the integration tests, which run the built binary.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A directory of its own below the system temp directory, removed on drop.
+pub struct Scratch(PathBuf);
+
+impl Scratch {
+    pub fn new(name: &str) -> Scratch {
+        let dir = std::env::temp_dir()
+            .join("create_woragis-tests")
+            .join(format!("{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Scratch(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+
+    /// The CLI in `dir` with its config and cache kept in the scratch
+    /// directory, so the user's own presets and templates stay out of it.
+    pub fn command(&self, dir: &Path, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_create_woragis_backend"));
+        command
+            .args(args)
+            .current_dir(dir)
+            .env("XDG_CONFIG_HOME", self.join("config"))
+            .env("XDG_CACHE_HOME", self.join("cache"));
+        command
+    }
+
+    /// Run [`Scratch::command`].
+    pub fn run(&self, dir: &Path, args: &[&str]) -> Output {
+        self.command(dir, args).output().unwrap()
+    }
+
+    /// [`Scratch::run`] in the scratch directory, failing the test unless it
+    /// succeeds.
+    pub fn ok(&self, args: &[&str]) -> String {
+        let output = self.run(self.path(), args);
+        assert!(
+            output.status.success(),
+            "{:?} failed:\n{}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Run `git` in `dir` as a fixed author, returning its trimmed stdout.
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?} failed:\n{}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// `cargo check` the project in `dir`, returning the compiler's complaints
+/// when it fails.
+pub fn cargo_check(dir: &Path) -> Result<(), String> {
+    cargo(dir, &["check", "--quiet", "--workspace"])
+}
+
+/// The lints the generated CI runs on the project in `dir`: `cargo fmt
+/// --check`, then `cargo clippy` with warnings denied.
+pub fn cargo_lint(dir: &Path) -> Result<(), String> {
+    cargo(dir, &["fmt", "--all", "--check"])?;
+    cargo_clippy(dir)
+}
+
+/// `cargo clippy` the project in `dir`, every target, with warnings denied.
+pub fn cargo_clippy(dir: &Path) -> Result<(), String> {
+    cargo(
+        dir,
+        &[
+            "clippy",
+            "--quiet",
+            "--workspace",
+            "--all-targets",
+            "--",
+            "-D",
+            "warnings",
+        ],
+    )
+}
+
+/// Runs cargo in `dir`, returning its output when it fails.
+///
+/// Every run shares one target directory (`CREATE_WORAGIS_TEST_TARGET`, or
+/// one below the system temp directory) so dependencies build only once.
+fn cargo(dir: &Path, args: &[&str]) -> Result<(), String> {
+    let target = std::env::var_os("CREATE_WORAGIS_TEST_TARGET")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("create_woragis-tests-target"));
+    let output = Command::new(env!("CARGO"))
+        .args(args)
+        .current_dir(dir)
+        .env("CARGO_TARGET_DIR", target)
+        .output()
+        .unwrap();
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("{}{}", stdout, stderr))
+    }
+}
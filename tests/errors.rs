@@ -0,0 +1,26 @@
+//! Errors as reported to scripts with `--json`.
+
+mod common;
+
+use common::Scratch;
+
+/// The `error.code` of the JSON printed by a failing run.
+fn code(scratch: &Scratch, args: &[&str]) -> String {
+    let output = scratch.run(scratch.path(), args);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    json["error"]["code"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn unknown_templates_are_not_usage_errors() {
+    let scratch = Scratch::new("errors-template");
+    for args in [
+        &["demo", "--json", "--template", "nope"][..],
+        &["demo", "--json", "-t", "nope", "--dry-run"],
+        &["demo", "--json", "--template-path", "missing"],
+    ] {
+        assert_eq!(code(&scratch, args), "unknown_template", "{:?}", args);
+    }
+    assert_eq!(code(&scratch, &["demo", "--json", "--nope"]), "usage");
+}
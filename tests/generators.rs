@@ -0,0 +1,83 @@
+//! `generate` on freshly generated projects.
+
+mod common;
+
+use std::fs;
+
+use common::{Scratch, cargo_clippy};
+
+#[test]
+fn resources_answer_404_for_missing_records() {
+    let scratch = Scratch::new("generate-resource");
+    scratch.ok(&["demo", "--author", "Test", "--features", ""]);
+    let project = scratch.join("demo");
+
+    let output = scratch.run(&project, &["generate", "resource", "todo", "title:string"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let controller = fs::read_to_string(project.join("src/controllers/todo.rs")).unwrap();
+    assert!(controller.contains("ApiError::NotFound("), "{}", controller);
+    assert!(!controller.contains("ApiError::Custom("), "{}", controller);
+    let readme = fs::read_to_string(project.join("README.md")).unwrap();
+    assert!(readme.contains("`404` when there is none"), "{}", readme);
+}
+
+/// Generates a resource in a fresh project of each framework and runs
+/// `cargo clippy -D warnings` on it. Needs the crates of the generated
+/// projects, from the network or the local registry; run with
+/// `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn generated_resources_compile() {
+    let scratch = Scratch::new("generate-resource-compile");
+    for framework in ["actix", "axum"] {
+        for db in ["postgres", "none"] {
+            let label = format!("{} with {}", framework, db);
+            let dir = scratch.join(label.replace(' ', "-"));
+            fs::create_dir_all(&dir).unwrap();
+            let output = scratch.run(
+                &dir,
+                &[
+                    "demo",
+                    "--author",
+                    "Test",
+                    "--framework",
+                    framework,
+                    "--db",
+                    db,
+                ],
+            );
+            assert!(output.status.success(), "{}: {:?}", label, output);
+            let project = dir.join("demo");
+            let output = scratch.run(
+                &project,
+                &["generate", "resource", "todo", "title:string", "done:bool"],
+            );
+            assert!(output.status.success(), "{}: {:?}", label, output);
+            if let Err(errors) = cargo_clippy(&project) {
+                panic!("{}:\n{}", label, errors);
+            }
+        }
+    }
+}
+
+#[test]
+fn failing_spec_resources_leave_no_project() {
+    let scratch = Scratch::new("spec-resources");
+    let spec = scratch.join("spec.yaml");
+    fs::write(
+        &spec,
+        "name: demo\nauthor: Test\nresources:\n  - name: todo\n    fields: [\"title:string\"]\n  - name: todo\n    fields: [\"done:bool\"]\n",
+    )
+    .unwrap();
+
+    let output = scratch.run(scratch.path(), &["--from", spec.to_str().unwrap()]);
+    assert!(!output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("already exists"), "{}", stderr);
+    let left: Vec<_> = fs::read_dir(scratch.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(left, ["spec.yaml"]);
+}
@@ -0,0 +1,43 @@
+//! `--git` where git has no identity of its own configured.
+
+mod common;
+
+use std::fs;
+
+use common::{Scratch, git};
+
+#[test]
+fn commits_as_the_author_without_a_git_identity() {
+    let scratch = Scratch::new("git-identity");
+    let home = scratch.join("home");
+    fs::create_dir_all(&home).unwrap();
+    let global = home.join(".gitconfig");
+    fs::write(&global, "").unwrap();
+
+    for (name, author, expected) in [
+        (
+            "full",
+            "Jane Doe <jane@example.com>",
+            "Jane Doe <jane@example.com>",
+        ),
+        ("bare", "Jane Doe", "Jane Doe <>"),
+    ] {
+        let output = scratch
+            .command(scratch.path(), &[name, "--author", author, "--git"])
+            .env("HOME", &home)
+            .env("GIT_CONFIG_GLOBAL", &global)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env_remove("GIT_AUTHOR_NAME")
+            .env_remove("GIT_AUTHOR_EMAIL")
+            .env_remove("GIT_COMMITTER_NAME")
+            .env_remove("GIT_COMMITTER_EMAIL")
+            .env_remove("EMAIL")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}: {:?}", author, output);
+
+        let project = scratch.join(name);
+        assert_eq!(git(&project, &["log", "--format=%an <%ae>"]), expected);
+        assert_eq!(git(&project, &["log", "--format=%cn <%ce>"]), expected);
+    }
+}
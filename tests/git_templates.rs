@@ -0,0 +1,125 @@
+//! Templates fetched from git, against a local bare repository so nothing
+//! goes over the network.
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{Scratch, git};
+
+/// A bare repository with a one-file template: `v1` is tagged, `main` is
+/// one commit later. Returns the URL to use it by and the commit of `v1`.
+fn template_repo(scratch: &Scratch) -> (String, String) {
+    let work = scratch.join("work");
+    fs::create_dir_all(&work).unwrap();
+    git(&work, &["init", "--quiet", "--initial-branch", "main"]);
+    fs::write(
+        work.join("template.toml"),
+        "[template]\ndescription = \"A git template\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    fs::write(work.join("README.md"), "# {{ project_name }} v1\n").unwrap();
+    git(&work, &["add", "."]);
+    git(&work, &["commit", "--quiet", "--message", "v1"]);
+    git(&work, &["tag", "v1"]);
+    let v1 = git(&work, &["rev-parse", "HEAD"]);
+    fs::write(work.join("README.md"), "# {{ project_name }} v2\n").unwrap();
+    git(&work, &["commit", "--quiet", "--all", "--message", "v2"]);
+
+    let bare = scratch.join("template.git");
+    git(
+        scratch.path(),
+        &["clone", "--quiet", "--bare", "work", "template.git"],
+    );
+    (format!("git+file://{}", bare.display()), v1)
+}
+
+fn lock(project: &Path) -> toml::Table {
+    fs::read_to_string(project.join(".woragis.toml"))
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn ref_pins_the_template_and_the_lock_records_its_commit() {
+    let scratch = Scratch::new("git-ref");
+    let (url, v1) = template_repo(&scratch);
+
+    let pinned = format!("{}#v1", url);
+    scratch.ok(&["pinned", "--author", "Test", "--template", &pinned]);
+    let readme = fs::read_to_string(scratch.join("pinned/README.md")).unwrap();
+    assert_eq!(readme, "# pinned v1\n");
+    let locked = lock(&scratch.join("pinned"));
+    assert_eq!(locked["template"]["source"].as_str(), Some(pinned.as_str()));
+    assert_eq!(locked["template"]["commit"].as_str(), Some(v1.as_str()));
+    assert_eq!(locked["template"]["version"].as_str(), Some("1.0.0"));
+
+    scratch.ok(&["latest", "--author", "Test", "--template", &url]);
+    let readme = fs::read_to_string(scratch.join("latest/README.md")).unwrap();
+    assert_eq!(readme, "# latest v2\n");
+    let locked = lock(&scratch.join("latest"));
+    assert_ne!(locked["template"]["commit"].as_str(), Some(v1.as_str()));
+}
+
+#[test]
+fn cached_templates_work_without_the_repository() {
+    let scratch = Scratch::new("git-cache");
+    let (url, v1) = template_repo(&scratch);
+    scratch.ok(&[
+        "first",
+        "--author",
+        "Test",
+        "--template",
+        &format!("{}#v1", url),
+    ]);
+    fs::rename(scratch.join("template.git"), scratch.join("moved.git")).unwrap();
+
+    // A full commit that is already cached needs no fetch at all
+    let by_commit = format!("{}#{}", url, v1);
+    let output = scratch.run(
+        scratch.path(),
+        &["second", "--author", "Test", "--template", &by_commit],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stderr.is_empty(), "{:?}", output);
+    let readme = fs::read_to_string(scratch.join("second/README.md")).unwrap();
+    assert_eq!(readme, "# second v1\n");
+
+    // A tag is fetched again, which fails, so the cached one is used
+    let output = scratch.run(
+        scratch.path(),
+        &[
+            "third",
+            "--author",
+            "Test",
+            "--template",
+            &format!("{}#v1", url),
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("using the cached copy"), "{}", stderr);
+    let locked = lock(&scratch.join("third"));
+    assert_eq!(locked["template"]["commit"].as_str(), Some(v1.as_str()));
+}
+
+#[test]
+fn unknown_refs_are_rejected() {
+    let scratch = Scratch::new("git-unknown");
+    let (url, _) = template_repo(&scratch);
+
+    let output = scratch.run(
+        scratch.path(),
+        &["demo", "--template", &format!("{}#v9", url)],
+    );
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("has no tag, branch or commit named 'v9'"),
+        "{}",
+        stderr
+    );
+    assert!(!scratch.join("demo").exists());
+}
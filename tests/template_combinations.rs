@@ -0,0 +1,82 @@
+//! Generated projects compile and pass the generated CI's lints, across a
+//! representative matrix of the `rest` template's options.
+//!
+//! `lint-templates` parses every combination but cannot type-check it, so
+//! this runs `cargo fmt --check` and `cargo clippy -D warnings` on each
+//! project of the matrix. It needs the crates of the generated projects,
+//! from the network or the local registry, and takes minutes; run it with
+//! `cargo test -- --ignored`.
+
+mod common;
+
+use std::fs;
+
+use common::{Scratch, cargo_lint};
+
+const ALL_FEATURES: &str = "auth,admin,cache,rate-limit,profile-picture";
+
+/// Every framework and database with no and with all features, then a few
+/// features on their own and the workspace layout.
+fn matrix() -> Vec<Vec<&'static str>> {
+    let mut matrix = Vec::new();
+    for framework in ["actix", "axum"] {
+        for db in ["postgres", "sqlite", "mysql", "none"] {
+            for features in ["", ALL_FEATURES] {
+                matrix.push(vec![
+                    "--framework",
+                    framework,
+                    "--db",
+                    db,
+                    "--features",
+                    features,
+                ]);
+            }
+        }
+    }
+    for (framework, db, features) in [
+        ("actix", "none", "cache"),
+        ("axum", "sqlite", "rate-limit"),
+        ("actix", "mysql", "auth,rate-limit"),
+        ("axum", "postgres", "profile-picture"),
+    ] {
+        matrix.push(vec![
+            "--framework",
+            framework,
+            "--db",
+            db,
+            "--features",
+            features,
+        ]);
+    }
+    for (framework, db, features) in [("actix", "postgres", ALL_FEATURES), ("axum", "none", "")] {
+        matrix.push(vec![
+            "--framework",
+            framework,
+            "--db",
+            db,
+            "--features",
+            features,
+            "--workspace",
+            "--with-worker",
+        ]);
+    }
+    matrix
+}
+
+#[test]
+#[ignore]
+fn generated_projects_compile() {
+    let scratch = Scratch::new("combinations");
+    let dir = scratch.join("demo");
+    let mut failures = Vec::new();
+    for options in matrix() {
+        let _ = fs::remove_dir_all(&dir);
+        let mut args = vec!["demo", "--author", "Test"];
+        args.extend(&options);
+        scratch.ok(&args);
+        if let Err(errors) = cargo_lint(&dir) {
+            failures.push(format!("{}:\n{}", options.join(" "), errors));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,109 @@
+//! `upgrade` of a project generated from a local template that changes.
+
+mod common;
+
+use std::fs;
+
+use common::Scratch;
+
+#[test]
+fn upgrade_merges_the_new_template_into_the_project() {
+    let scratch = Scratch::new("upgrade");
+    let template = scratch.join("template");
+    fs::create_dir_all(template.join("src")).unwrap();
+    let manifest = |version: &str| {
+        format!(
+            "[template]\ndescription = \"A local template\"\nversion = \"{}\"\n",
+            version
+        )
+    };
+    fs::write(template.join("template.toml"), manifest("1.0.0")).unwrap();
+    fs::write(
+        template.join("Cargo.toml"),
+        "[package]\nname = \"{{ crate_name }}\"\nversion = \"0.1.0\"\nedition = \"2024\"\n",
+    )
+    .unwrap();
+    fs::write(
+        template.join("README.md"),
+        "# {{ project_name }}\n\nA service.\n\n## Running\n\ncargo run\n",
+    )
+    .unwrap();
+    fs::write(
+        template.join("src/main.rs"),
+        "fn main() {\n    println!(\"v1\");\n}\n",
+    )
+    .unwrap();
+    fs::write(template.join("old.txt"), "dropped in 1.1.0\n").unwrap();
+
+    let template_path = template.to_string_lossy().into_owned();
+    scratch.ok(&[
+        "demo",
+        "--author",
+        "Test",
+        "--template-path",
+        &template_path,
+    ]);
+    let project = scratch.join("demo");
+    let read = |path: &str| fs::read_to_string(project.join(path)).unwrap();
+
+    // The user's changes: one apart from the template's, one overlapping
+    let readme = format!("{}\n## Deploying\n\nAsk ops.\n", read("README.md"));
+    fs::write(project.join("README.md"), &readme).unwrap();
+    fs::write(
+        project.join("src/main.rs"),
+        "fn main() {\n    println!(\"ours\");\n}\n",
+    )
+    .unwrap();
+
+    fs::write(template.join("template.toml"), manifest("1.1.0")).unwrap();
+    fs::write(
+        template.join("README.md"),
+        "# {{ project_name }}\n\nA small service.\n\n## Running\n\ncargo run\n",
+    )
+    .unwrap();
+    fs::write(
+        template.join("src/main.rs"),
+        "fn main() {\n    println!(\"v2\");\n}\n",
+    )
+    .unwrap();
+    fs::remove_file(template.join("old.txt")).unwrap();
+    fs::write(template.join("new.txt"), "added in 1.1.0\n").unwrap();
+
+    let output = scratch.run(&project, &["upgrade", "--dry-run"]);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Would upgrade from 'template' 1.0.0 to 'template' 1.1.0"),
+        "{}",
+        stdout
+    );
+    assert_eq!(read("README.md"), readme);
+    assert!(project.join("old.txt").exists());
+    assert!(!project.join("new.txt").exists());
+
+    let output = scratch.run(&project, &["upgrade"]);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("updated      README.md"), "{}", stdout);
+    assert!(stdout.contains("conflict     src/main.rs"), "{}", stdout);
+    assert!(stdout.contains("removed      old.txt"), "{}", stdout);
+    assert!(stdout.contains("created      new.txt"), "{}", stdout);
+    assert!(stdout.contains("1 file(s) have conflicts"), "{}", stdout);
+
+    assert_eq!(
+        read("README.md"),
+        "# demo\n\nA small service.\n\n## Running\n\ncargo run\n\n## Deploying\n\nAsk ops.\n"
+    );
+    let main = read("src/main.rs");
+    assert!(main.contains("<<<<<<<"), "{}", main);
+    assert!(main.contains("println!(\"ours\");"), "{}", main);
+    assert!(main.contains("println!(\"v2\");"), "{}", main);
+    assert!(!project.join("old.txt").exists());
+    assert_eq!(read("new.txt"), "added in 1.1.0\n");
+    let lock: toml::Table = read(".woragis.toml").parse().unwrap();
+    assert_eq!(lock["template"]["version"].as_str(), Some("1.1.0"));
+
+    let output = scratch.run(&project, &["upgrade"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Already up to date"), "{}", stdout);
+}